@@ -0,0 +1,378 @@
+//! # Configuration Module
+//!
+//! This module loads the game configuration from a TOML file and exposes the
+//! game rules to the rest of the program.
+//!
+//! ## Features
+//!
+//! - Game rules (daily limits, starting stats, leveling, rewards, prices)
+//! - Game-day boundary: the time of day and timezone of the daily reset
+//! - Session limits: daily play time per player and idle timeout
+//! - News retention: how long the daily news is kept
+//! - Defaults that match the classic pacing when no file is present
+//! - Partial files: any setting left out keeps its default
+//!
+//! ## Implementation Details
+//!
+//! The configuration is read once at startup by `load` and stored in a global
+//! so game modules can consult `rules()` without threading it through every
+//! call. The file is `lord.toml` in the working directory unless the
+//! `LORD_CONFIG` environment variable points elsewhere.
+//!
+//! ```toml
+//! [rules]
+//! max_daily_forest_fights = 15
+//! starting_gold = 250
+//! duel_loot_percent = 25
+//!
+//! [day]
+//! reset_time = "04:00"
+//! timezone = "America/New_York"
+//!
+//! [session]
+//! daily_minutes = 30
+//! idle_timeout_minutes = 5
+//!
+//! [news]
+//! retention_days = 30
+//! ```
+use chrono::NaiveTime;
+use chrono_tz::Tz;
+use serde::Deserialize;
+use std::env;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::sync::OnceLock;
+
+/// Configuration file used when `LORD_CONFIG` is not set.
+pub const DEFAULT_CONFIG_PATH: &str = "lord.toml";
+
+/// The configuration loaded at startup.
+static CONFIG: OnceLock<Config> = OnceLock::new();
+
+/// Top-level structure of the configuration file.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Game rules consulted by the game modules
+    pub rules: Rules,
+    /// When one game day ends and the next begins
+    pub day: DayConfig,
+    /// How long players may stay connected
+    pub session: SessionConfig,
+    /// How long the daily news is kept
+    pub news: NewsConfig,
+}
+
+/// Tunable game rules.
+///
+/// Every field has a default matching the classic pacing of the game, so a
+/// configuration file only needs to list the rules a board wants to change.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Rules {
+    /// Forest fights granted to every player each day
+    pub max_daily_forest_fights: i32,
+    /// Duels every player may start each day
+    pub max_daily_player_fights: i32,
+    /// Hours after creation during which a new character cannot be challenged (0 disables)
+    pub new_player_protection_hours: i32,
+    /// Gold carried by a freshly created character
+    pub starting_gold: i32,
+    /// Hit points (current and maximum) of a freshly created character
+    pub starting_hp: i32,
+    /// Attack power of a freshly created character
+    pub starting_attack: i32,
+    /// Defense power of a freshly created character
+    pub starting_defense: i32,
+    /// Experience needed per level: reaching level N+1 takes N times this amount
+    pub xp_per_level: i32,
+    /// Maximum hit points gained on each level-up
+    pub level_up_hp: i32,
+    /// Attack power gained on each level-up
+    pub level_up_attack: i32,
+    /// Defense power gained on each level-up
+    pub level_up_defense: i32,
+    /// Percentage of the loser's gold taken by the winner of a duel
+    pub duel_loot_percent: i32,
+    /// Experience awarded per level of a defeated duel opponent
+    pub duel_xp_per_level: i32,
+    /// How many levels below themselves a hero may challenge (0 = no restriction)
+    pub duel_level_range: i32,
+    /// Percentage of loot and experience lost per level the defeated opponent is below the winner
+    pub duel_weaker_penalty_percent: i32,
+    /// PvP rating of a freshly created character
+    pub starting_rating: i32,
+    /// Largest rating change a single duel can cause (the Elo K-factor)
+    pub rating_k_factor: i32,
+    /// Price of a drink at the tavern
+    pub drink_price: i32,
+    /// Gold the healer charges per hit point restored
+    pub healer_price_per_hp: i32,
+    /// Gold the healer charges to cure poison
+    pub cure_poison_price: i32,
+    /// Smallest bounty that may be posted on a hero's head
+    pub min_bounty: i32,
+    /// Extra forest fights granted by the bard's song of the hunt
+    pub bard_forest_fights: i32,
+    /// Extra attack granted by a strength buff until the daily reset
+    pub buff_strength: i32,
+    /// Gold each hero may wager at the tavern's gaming tables per day
+    pub max_daily_wager: i32,
+    /// Percentage of every gambling win kept by the house
+    pub house_edge_percent: i32,
+    /// Smallest gambling win announced in the news
+    pub big_win_gold: i32,
+    /// Percent chance of finding gems on a slain forest monster
+    pub gem_find_chance: i32,
+    /// Gems the bartender asks for each stat boost
+    pub gem_trade_price: i32,
+    /// Maximum hit points gained from one gem trade for vitality
+    pub gem_trade_max_hp: i32,
+    /// Level a hero must reach before they can seek out the Red Dragon
+    pub red_dragon_level: i32,
+    /// Red Dragon kills by a single hero that end the season (0 disables)
+    pub season_dragon_kills: i32,
+}
+
+impl Default for Rules {
+    fn default() -> Self {
+        Rules {
+            max_daily_forest_fights: 10,
+            max_daily_player_fights: 3,
+            new_player_protection_hours: 24,
+            starting_gold: 100,
+            starting_hp: 20,
+            starting_attack: 5,
+            starting_defense: 2,
+            xp_per_level: 100,
+            level_up_hp: 10,
+            level_up_attack: 2,
+            level_up_defense: 1,
+            duel_loot_percent: 50,
+            duel_xp_per_level: 50,
+            duel_level_range: 3,
+            duel_weaker_penalty_percent: 25,
+            starting_rating: 1200,
+            rating_k_factor: 32,
+            drink_price: 5,
+            healer_price_per_hp: 1,
+            cure_poison_price: 50,
+            min_bounty: 100,
+            bard_forest_fights: 2,
+            buff_strength: 2,
+            max_daily_wager: 1000,
+            house_edge_percent: 5,
+            big_win_gold: 500,
+            gem_find_chance: 5,
+            gem_trade_price: 2,
+            gem_trade_max_hp: 5,
+            red_dragon_level: 12,
+            season_dragon_kills: 0,
+        }
+    }
+}
+
+/// Game-day boundary settings.
+///
+/// A game day starts at `reset_time` in `timezone` and lasts until the same
+/// time the next day. The daily reset runs once per boundary crossed.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DayConfig {
+    /// Time of day at which the daily reset happens (e.g. "04:00")
+    pub reset_time: NaiveTime,
+    /// IANA timezone of the reset time (e.g. "Europe/London"); the server's local time if unset
+    pub timezone: Option<Tz>,
+}
+
+impl Default for DayConfig {
+    fn default() -> Self {
+        DayConfig {
+            reset_time: NaiveTime::MIN,
+            timezone: None,
+        }
+    }
+}
+
+/// Session time limits, used by door hosts to free up nodes.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SessionConfig {
+    /// Minutes each player may play per game day (0 = unlimited)
+    pub daily_minutes: i32,
+    /// Minutes without input before the session is saved and closed (0 = never)
+    pub idle_timeout_minutes: i32,
+    /// Warn the player once this many minutes or fewer of their daily time remain
+    pub warning_minutes: i32,
+    /// Seconds without a heartbeat after which a player's online lock is considered stale
+    pub lock_timeout_seconds: i32,
+    /// Seed for the game's random number generator, making every session's dice reproducible (random if unset)
+    pub seed: Option<u64>,
+}
+
+impl Default for SessionConfig {
+    fn default() -> Self {
+        SessionConfig {
+            daily_minutes: 0,
+            idle_timeout_minutes: 0,
+            warning_minutes: 5,
+            lock_timeout_seconds: 120,
+            seed: None,
+        }
+    }
+}
+
+impl Config {
+    /// Check that every numeric setting is within the range the game can work with.
+    ///
+    /// Zero experience per level would level a hero up forever, a hero with no
+    /// attack cannot roll damage, and percentages above 100 or negative prices
+    /// would hand out gold from nowhere.
+    fn validate(&self) -> Result<(), String> {
+        const ANY: i32 = i32::MAX;
+        let r = &self.rules;
+        let ranges = [
+            ("rules.max_daily_forest_fights", r.max_daily_forest_fights, 0, ANY),
+            ("rules.max_daily_player_fights", r.max_daily_player_fights, 0, ANY),
+            ("rules.new_player_protection_hours", r.new_player_protection_hours, 0, ANY),
+            ("rules.starting_gold", r.starting_gold, 0, ANY),
+            ("rules.starting_hp", r.starting_hp, 1, ANY),
+            ("rules.starting_attack", r.starting_attack, 1, ANY),
+            ("rules.starting_defense", r.starting_defense, 0, ANY),
+            ("rules.xp_per_level", r.xp_per_level, 1, ANY),
+            ("rules.level_up_hp", r.level_up_hp, 0, ANY),
+            ("rules.level_up_attack", r.level_up_attack, 0, ANY),
+            ("rules.level_up_defense", r.level_up_defense, 0, ANY),
+            ("rules.duel_loot_percent", r.duel_loot_percent, 0, 100),
+            ("rules.duel_xp_per_level", r.duel_xp_per_level, 0, ANY),
+            ("rules.duel_level_range", r.duel_level_range, 0, ANY),
+            ("rules.duel_weaker_penalty_percent", r.duel_weaker_penalty_percent, 0, 100),
+            ("rules.starting_rating", r.starting_rating, 0, ANY),
+            ("rules.rating_k_factor", r.rating_k_factor, 0, ANY),
+            ("rules.drink_price", r.drink_price, 0, ANY),
+            ("rules.healer_price_per_hp", r.healer_price_per_hp, 0, ANY),
+            ("rules.cure_poison_price", r.cure_poison_price, 0, ANY),
+            ("rules.min_bounty", r.min_bounty, 1, ANY),
+            ("rules.bard_forest_fights", r.bard_forest_fights, 0, ANY),
+            ("rules.buff_strength", r.buff_strength, 0, ANY),
+            ("rules.max_daily_wager", r.max_daily_wager, 0, ANY),
+            ("rules.house_edge_percent", r.house_edge_percent, 0, 100),
+            ("rules.big_win_gold", r.big_win_gold, 0, ANY),
+            ("rules.gem_find_chance", r.gem_find_chance, 0, 100),
+            ("rules.gem_trade_price", r.gem_trade_price, 1, ANY),
+            ("rules.gem_trade_max_hp", r.gem_trade_max_hp, 0, ANY),
+            ("rules.red_dragon_level", r.red_dragon_level, 1, ANY),
+            ("rules.season_dragon_kills", r.season_dragon_kills, 0, ANY),
+            ("session.daily_minutes", self.session.daily_minutes, 0, ANY),
+            ("session.idle_timeout_minutes", self.session.idle_timeout_minutes, 0, ANY),
+            ("session.warning_minutes", self.session.warning_minutes, 0, ANY),
+            ("session.lock_timeout_seconds", self.session.lock_timeout_seconds, 1, ANY),
+            ("news.retention_days", self.news.retention_days, 0, ANY),
+        ];
+        for (name, value, min, max) in ranges {
+            check_range(name, value, min, max)?;
+        }
+        Ok(())
+    }
+}
+
+/// Check that a setting named `name` lies within `min..=max`, describing the problem if not.
+///
+/// The data catalogs use this too, so every file the game loads reports a bad
+/// number the same way.
+pub fn check_range(name: &str, value: i32, min: i32, max: i32) -> Result<(), String> {
+    if value < min {
+        return Err(format!("{} must be at least {} (got {})", name, min, value));
+    }
+    if value > max {
+        return Err(format!("{} must be at most {} (got {})", name, max, value));
+    }
+    Ok(())
+}
+
+/// Daily news settings.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct NewsConfig {
+    /// Game days of news to keep; older entries are pruned by the daily reset (0 = keep forever)
+    pub retention_days: i32,
+}
+
+/// Errors that can occur while loading the configuration file.
+#[derive(Debug)]
+pub enum ConfigError {
+    /// The file exists but could not be read
+    Io(String, io::Error),
+    /// The file is not valid TOML or contains unknown settings
+    Parse(String, toml::de::Error),
+    /// A setting is outside the range the game can work with
+    Invalid(String, String),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Io(path, e) => write!(f, "could not read {}: {}", path, e),
+            ConfigError::Parse(path, e) => write!(f, "invalid configuration in {}: {}", path, e),
+            ConfigError::Invalid(path, e) => write!(f, "invalid configuration in {}: {}", path, e),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+/// Load the configuration file and make it available through `get` and `rules`.
+///
+/// This function:
+/// 1. Resolves the path from `LORD_CONFIG`, falling back to `lord.toml`
+/// 2. Uses the defaults if the file does not exist
+/// 3. Parses the file, filling in defaults for missing settings
+/// 4. Checks that every setting is within its allowed range
+/// 5. Stores the result for the rest of the program
+///
+/// # Errors
+///
+/// Returns a `ConfigError` if the file exists but cannot be read or parsed,
+/// or if a setting is out of range.
+pub fn load() -> Result<&'static Config, ConfigError> {
+    let path = env::var("LORD_CONFIG").unwrap_or_else(|_| DEFAULT_CONFIG_PATH.to_string());
+    let config: Config = match fs::read_to_string(&path) {
+        Ok(text) => toml::from_str(&text).map_err(|e| ConfigError::Parse(path.clone(), e))?,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Config::default(),
+        Err(e) => return Err(ConfigError::Io(path, e)),
+    };
+    config.validate().map_err(|e| ConfigError::Invalid(path, e))?;
+    Ok(CONFIG.get_or_init(|| config))
+}
+
+/// The configuration loaded at startup, or the defaults if `load` was never called.
+pub fn get() -> &'static Config {
+    CONFIG.get_or_init(Config::default)
+}
+
+/// The game rules in effect.
+pub fn rules() -> &'static Rules {
+    &get().rules
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn defaults_are_valid() {
+        assert_eq!(Config::default().validate(), Ok(()));
+    }
+
+    #[test]
+    fn out_of_range_rules_are_rejected() {
+        let config: Config = toml::from_str("[rules]\nxp_per_level = 0").unwrap();
+        assert_eq!(config.validate(), Err("rules.xp_per_level must be at least 1 (got 0)".to_string()));
+        let config: Config = toml::from_str("[rules]\nhouse_edge_percent = 150").unwrap();
+        assert_eq!(config.validate(), Err("rules.house_edge_percent must be at most 100 (got 150)".to_string()));
+        let config: Config = toml::from_str("[rules]\ndrink_price = -5").unwrap();
+        assert!(config.validate().is_err());
+    }
+}
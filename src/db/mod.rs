@@ -7,7 +7,7 @@
 //!
 //! - Connection pooling with configurable connection limits  
 //! - Secure password storage using Argon2 hashing algorithm  
-//! - Daily game state reset mechanism  
+//! - Daily game state reset mechanism with catch-up for missed days  
 //! - Player data persistence and retrieval  
 //! - Game event logging and history  
 //! - Player rankings and statistics  
@@ -22,14 +22,24 @@
 //!
 //! ## Database Schema
 //!
-//! The database consists of three main tables:  
-//! - `players`: Stores player data including stats, inventory, and authentication  
-//! - `news`: Records game events and player achievements  
+//! The main tables are:  
+//! - `players`: Stores player data including stats, gold, and authentication  
+//! - `events`: Structured record of every game event (who, to whom, gold, experience, details)  
+//! - `news`: The daily news rendered from events, plus announcements, by category and game day  
+//! - `inventory`: Stacks of items carried by each player  
+//! - `player_buffs`: Temporary effects that last until the next daily reset  
+//! - `player_stats`, `player_monster_kills`: Lifetime statistics kept up to date from the events  
 //! - `game_state`: Maintains global game state including daily reset tracking  
+//!
+//! Finished seasons are archived in `seasons`, `season_players` and `season_news`.
+//! Players currently logged in hold a lock in `online_players`, which also records
+//! their node and location; `chat_messages` carries messages between nodes.
 //! 
-use sqlx::{PgPool, postgres::PgPoolOptions};
-use crate::game::Player;
-use chrono::{Local, NaiveDateTime};
+use sqlx::{PgConnection, PgPool, postgres::PgPoolOptions};
+use crate::config::{self, rules};
+use crate::game::{calendar, GameEvent, Player};
+use sqlx::types::Json;
+use chrono::{NaiveDate, NaiveDateTime, Utc};
 use dotenvy::dotenv;
 use std::env;
 // Password hashing dependencies
@@ -52,6 +62,322 @@ pub struct PlayerInfo {
     pub level: i32,
 }
 
+/// A player who is currently logged in, for the "Who's Online" list.
+#[derive(Debug, Clone, PartialEq, Eq, sqlx::FromRow)]
+pub struct OnlinePlayer {
+    /// Node number the player is connected on
+    pub node: i32,
+    /// Unique player identifier
+    pub player_id: i32,
+    /// Player's character name
+    pub name: String,
+    /// Player's experience level
+    pub level: i32,
+    /// Where in the realm the player is (e.g. "Forest")
+    pub location: String,
+    /// When the player logged in
+    pub started_at: NaiveDateTime,
+}
+
+/// A chat message delivered to a node.
+#[derive(Debug, Clone, PartialEq, Eq, sqlx::FromRow)]
+pub struct ChatMessage {
+    /// Unique, increasing message identifier
+    pub id: i32,
+    /// Name of the sender
+    pub from_name: String,
+    /// Whether the message was sent to every node rather than just this one
+    pub broadcast: bool,
+    /// Message text
+    pub body: String,
+}
+
+/// Category of a news entry, used to filter the daily news.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NewsKind {
+    /// A hero gained a level
+    LevelUp,
+    /// A hero defeated a monster in the forest
+    Forest,
+    /// The outcome of a duel between two heroes
+    Duel,
+    /// A hero died outside the arena
+    Death,
+    /// A hero got married
+    Marriage,
+    /// A hero slew the Red Dragon
+    Dragon,
+    /// Bounties posted and claimed
+    Bounty,
+    /// Big wins at the tavern's gaming tables
+    Tavern,
+    /// Announcements from the game itself (new days, new seasons)
+    System,
+}
+
+impl NewsKind {
+    /// Every category, in the order offered to players.
+    pub const ALL: [NewsKind; 9] = [
+        NewsKind::LevelUp,
+        NewsKind::Forest,
+        NewsKind::Duel,
+        NewsKind::Death,
+        NewsKind::Marriage,
+        NewsKind::Dragon,
+        NewsKind::Bounty,
+        NewsKind::Tavern,
+        NewsKind::System,
+    ];
+
+    /// The value stored in the `kind` column.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            NewsKind::LevelUp => "level_up",
+            NewsKind::Forest => "forest",
+            NewsKind::Duel => "duel",
+            NewsKind::Death => "death",
+            NewsKind::Marriage => "marriage",
+            NewsKind::Dragon => "dragon",
+            NewsKind::Bounty => "bounty",
+            NewsKind::Tavern => "tavern",
+            NewsKind::System => "system",
+        }
+    }
+
+    /// Name of the category as shown to players.
+    pub fn label(self) -> &'static str {
+        match self {
+            NewsKind::LevelUp => "Level-ups",
+            NewsKind::Forest => "Forest victories",
+            NewsKind::Duel => "Duels",
+            NewsKind::Death => "Deaths",
+            NewsKind::Marriage => "Marriages",
+            NewsKind::Dragon => "Dragon slayings",
+            NewsKind::Bounty => "Bounties",
+            NewsKind::Tavern => "Tavern winnings",
+            NewsKind::System => "Announcements",
+        }
+    }
+}
+
+impl From<String> for NewsKind {
+    /// Parse a stored `kind`; anything unrecognised is treated as an announcement.
+    fn from(kind: String) -> Self {
+        NewsKind::ALL
+            .into_iter()
+            .find(|k| k.as_str() == kind)
+            .unwrap_or(NewsKind::System)
+    }
+}
+
+/// An entry in the daily news.
+#[derive(Debug, Clone, PartialEq, Eq, sqlx::FromRow)]
+pub struct NewsEntry {
+    /// Unique, increasing entry identifier
+    pub id: i32,
+    /// When the event happened
+    pub date: NaiveDateTime,
+    /// The game day the event belongs to
+    pub game_day: NaiveDate,
+    /// Category of the event
+    #[sqlx(try_from = "String")]
+    pub kind: NewsKind,
+    /// The player who caused the event, if any
+    pub actor_id: Option<i32>,
+    /// The other player involved (e.g. the loser of a duel), if any
+    pub target_id: Option<i32>,
+    /// Event text
+    pub message: String,
+}
+
+/// Which news entries to show.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct NewsFilter {
+    /// Only entries of this category (None = all categories)
+    pub kind: Option<NewsKind>,
+    /// Only entries involving this player, as actor or target (None = everyone)
+    pub involving: Option<i32>,
+}
+
+/// The open bounties on one hero's head, for the bounty board.
+#[derive(Debug, Clone, PartialEq, Eq, sqlx::FromRow)]
+pub struct BountyTotal {
+    /// The hero with a price on their head
+    pub target_id: i32,
+    /// The hero's character name
+    pub target_name: String,
+    /// The hero's experience level
+    pub target_level: i32,
+    /// Gold held in escrow for whoever defeats the hero
+    pub total: i64,
+    /// Number of bounties posted on the hero
+    pub posted: i64,
+}
+
+/// A ranked board of players.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Leaderboard {
+    /// Highest level and experience
+    Experience,
+    /// Most gold on hand
+    Wealth,
+    /// Most duels won, over the hero's lifetime
+    PvpWins,
+    /// Most Red Dragons slain this season
+    DragonKills,
+    /// Most days played, over the hero's lifetime
+    Oldest,
+    /// Highest PvP rating
+    Rating,
+}
+
+impl Leaderboard {
+    /// Every board, in the order offered to players.
+    pub const ALL: [Leaderboard; 6] = [
+        Leaderboard::Experience,
+        Leaderboard::Wealth,
+        Leaderboard::PvpWins,
+        Leaderboard::Rating,
+        Leaderboard::DragonKills,
+        Leaderboard::Oldest,
+    ];
+
+    /// Name of the board as shown to players.
+    pub fn label(self) -> &'static str {
+        match self {
+            Leaderboard::Experience => "Hall of Fame (experience)",
+            Leaderboard::Wealth => "Wealthiest Heroes",
+            Leaderboard::PvpWins => "Greatest Duelists",
+            Leaderboard::DragonKills => "Dragon Slayers",
+            Leaderboard::Oldest => "Oldest Heroes (days played)",
+            Leaderboard::Rating => "Arena Rankings (PvP rating)",
+        }
+    }
+
+    /// Heading of the column showing each hero's score on this board.
+    pub fn score_label(self) -> &'static str {
+        match self {
+            Leaderboard::Experience => "Exp",
+            Leaderboard::Wealth => "Gold",
+            Leaderboard::PvpWins => "Wins",
+            Leaderboard::DragonKills => "Dragons",
+            Leaderboard::Oldest => "Days",
+            Leaderboard::Rating => "Rating",
+        }
+    }
+
+    /// SQL expression for the score column.
+    fn score_sql(self) -> &'static str {
+        match self {
+            Leaderboard::Experience => "p.exp::BIGINT",
+            Leaderboard::Wealth => "p.gold::BIGINT",
+            Leaderboard::PvpWins => "s.duels_won::BIGINT",
+            Leaderboard::DragonKills => "p.dragon_kills::BIGINT",
+            Leaderboard::Oldest => "s.days_played::BIGINT",
+            Leaderboard::Rating => "p.rating::BIGINT",
+        }
+    }
+
+    /// SQL source of the board's rows.
+    ///
+    /// Boards ranked by lifetime statistics are read from `player_stats`, so
+    /// that its indexes can drive the ordering; every hero has a row there.
+    fn source_sql(self) -> &'static str {
+        match self {
+            Leaderboard::PvpWins | Leaderboard::Oldest => "player_stats s JOIN players p ON p.id = s.player_id",
+            _ => "players p",
+        }
+    }
+
+    /// SQL ordering of the board; each one is backed by an index.
+    fn order_sql(self) -> &'static str {
+        match self {
+            Leaderboard::Experience => "p.level DESC, p.exp DESC, p.id",
+            Leaderboard::Wealth => "p.gold DESC, p.id",
+            Leaderboard::PvpWins => "s.duels_won DESC, s.player_id",
+            Leaderboard::DragonKills => "p.dragon_kills DESC, p.level DESC, p.exp DESC, p.id",
+            Leaderboard::Oldest => "s.days_played DESC, s.player_id",
+            Leaderboard::Rating => "p.rating DESC, p.id",
+        }
+    }
+}
+
+/// A hero's line on a leaderboard.
+#[derive(Debug, Clone, PartialEq, Eq, sqlx::FromRow)]
+pub struct LeaderboardEntry {
+    /// Unique player identifier
+    pub id: i32,
+    /// Player's character name
+    pub name: String,
+    /// Player's experience level
+    pub level: i32,
+    /// Whether the hero is alive today
+    pub alive: bool,
+    /// Whether the hero is playing right now
+    pub online: bool,
+    /// The hero's score on the board (see `Leaderboard::score_label`)
+    pub score: i64,
+}
+
+/// A player's lifetime statistics, which survive season rollovers.
+#[derive(Debug, Clone, Default, PartialEq, Eq, sqlx::FromRow)]
+pub struct PlayerStats {
+    /// Monsters slain in the forest
+    pub monsters_killed: i32,
+    /// Deaths in the forest, to the Red Dragon or in duels
+    pub deaths: i32,
+    /// Duels won
+    pub duels_won: i32,
+    /// Duels lost
+    pub duels_lost: i32,
+    /// Gold taken from monsters and duels
+    pub gold_earned: i64,
+    /// Gold spent in town
+    pub gold_spent: i64,
+    /// Highest level ever reached
+    pub highest_level: i32,
+    /// Game days on which the player logged in
+    pub days_played: i32,
+    /// Red Dragons slain, across all seasons
+    pub dragon_kills: i32,
+}
+
+/// Changes to one player's lifetime statistics caused by a single event.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StatsDelta {
+    /// Monster slain, counted overall and by type
+    pub monster: Option<String>,
+    /// Deaths to add
+    pub deaths: i32,
+    /// Duels won to add
+    pub duels_won: i32,
+    /// Duels lost to add
+    pub duels_lost: i32,
+    /// Gold earned to add
+    pub gold_earned: i64,
+    /// Gold spent to add
+    pub gold_spent: i64,
+    /// Level reached (0 = unchanged)
+    pub highest_level: i32,
+    /// Dragon kills to add
+    pub dragon_kills: i32,
+}
+
+/// Summary of a season that was closed by `end_season`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SeasonSummary {
+    /// Identifier of the season that just ended
+    pub season_id: i32,
+    /// When the finished season started
+    pub started_at: NaiveDateTime,
+    /// Names of the champion(s) of the finished season
+    pub champions: Vec<String>,
+    /// Number of player records copied into the archive
+    pub archived_players: u64,
+    /// Number of news entries copied into the archive
+    pub archived_news: u64,
+}
+
 /// Initialize the PostgreSQL connection pool and set up the database schema.
 /// 
 /// This function:  
@@ -80,10 +406,15 @@ pub async fn init_db_pool() -> Result<PgPool, sqlx::Error> {
         Err(e) => return Err(sqlx::Error::Configuration(Box::new(e))),
     };
 
+    connect_and_migrate(&database_url).await
+}
+
+/// Connect to the database at `database_url` and bring its schema up to date.
+async fn connect_and_migrate(database_url: &str) -> Result<PgPool, sqlx::Error> {
     // Create a connection pool with a maximum of 10 connections
     let pool = PgPoolOptions::new()
         .max_connections(10)
-        .connect(&database_url)
+        .connect(database_url)
         .await?;
 
     // Create the players table if it doesn't exist
@@ -105,10 +436,45 @@ pub async fn init_db_pool() -> Result<PgPool, sqlx::Error> {
             alive BOOLEAN,
             romance INTEGER,
             spouse TEXT,
-            last_login TIMESTAMP
+            last_login TIMESTAMP,
+            dragon_kills INTEGER NOT NULL DEFAULT 0,
+            time_used INTEGER NOT NULL DEFAULT 0,
+            version INTEGER NOT NULL DEFAULT 0
         )
         "#).execute(&pool).await?;
 
+    // Bring tables created by older versions up to date
+    sqlx::query("ALTER TABLE players ADD COLUMN IF NOT EXISTS dragon_kills INTEGER NOT NULL DEFAULT 0")
+        .execute(&pool).await?;
+    sqlx::query("ALTER TABLE players ADD COLUMN IF NOT EXISTS time_used INTEGER NOT NULL DEFAULT 0")
+        .execute(&pool).await?;
+    sqlx::query("ALTER TABLE players ADD COLUMN IF NOT EXISTS version INTEGER NOT NULL DEFAULT 0")
+        .execute(&pool).await?;
+    sqlx::query("ALTER TABLE players ADD COLUMN IF NOT EXISTS rating INTEGER NOT NULL DEFAULT 1200")
+        .execute(&pool).await?;
+    // Existing characters start with a full day's allowance of player fights
+    sqlx::query(&format!(
+        "ALTER TABLE players ADD COLUMN IF NOT EXISTS player_fights INTEGER NOT NULL DEFAULT {}",
+        rules().max_daily_player_fights
+    ))
+    .execute(&pool).await?;
+    // Existing characters have no creation time and are never treated as new
+    sqlx::query("ALTER TABLE players ADD COLUMN IF NOT EXISTS created_at TIMESTAMP")
+        .execute(&pool).await?;
+    sqlx::query("ALTER TABLE players ADD COLUMN IF NOT EXISTS gems INTEGER NOT NULL DEFAULT 0")
+        .execute(&pool).await?;
+    sqlx::query("ALTER TABLE players ADD COLUMN IF NOT EXISTS gold_wagered INTEGER NOT NULL DEFAULT 0")
+        .execute(&pool).await?;
+    sqlx::query("ALTER TABLE players ADD COLUMN IF NOT EXISTS poison INTEGER NOT NULL DEFAULT 0")
+        .execute(&pool).await?;
+    // Boosts bought with gems, reapplied when a Red Dragon slayer is reborn
+    sqlx::query("ALTER TABLE players ADD COLUMN IF NOT EXISTS gem_max_hp INTEGER NOT NULL DEFAULT 0")
+        .execute(&pool).await?;
+    sqlx::query("ALTER TABLE players ADD COLUMN IF NOT EXISTS gem_attack INTEGER NOT NULL DEFAULT 0")
+        .execute(&pool).await?;
+    sqlx::query("ALTER TABLE players ADD COLUMN IF NOT EXISTS gem_defense INTEGER NOT NULL DEFAULT 0")
+        .execute(&pool).await?;
+
     // Create the news table if it doesn't exist
     // This table stores game events and announcements
     sqlx::query(
@@ -119,6 +485,108 @@ pub async fn init_db_pool() -> Result<PgPool, sqlx::Error> {
             message TEXT
         )
         "#).execute(&pool).await?;
+    // Create the events table if it doesn't exist
+    // This table is the structured record of every game event; news is rendered from it
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS events (
+            id SERIAL PRIMARY KEY,
+            occurred_at TIMESTAMP NOT NULL DEFAULT NOW(),
+            game_day DATE NOT NULL,
+            season_id INTEGER,
+            kind TEXT NOT NULL,
+            actor_id INTEGER REFERENCES players(id) ON DELETE SET NULL,
+            target_id INTEGER REFERENCES players(id) ON DELETE SET NULL,
+            gold INTEGER NOT NULL DEFAULT 0,
+            exp INTEGER NOT NULL DEFAULT 0,
+            payload JSONB NOT NULL DEFAULT '{}'
+        )
+        "#).execute(&pool).await?;
+
+    // Create the inventory table if it doesn't exist
+    // Items are defined in the item catalog; each row is a stack of one kind
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS inventory (
+            player_id INTEGER REFERENCES players(id) ON DELETE CASCADE,
+            item_id TEXT NOT NULL,
+            quantity INTEGER NOT NULL CHECK (quantity > 0),
+            PRIMARY KEY (player_id, item_id)
+        )
+        "#).execute(&pool).await?;
+
+    // Create the player_buffs table if it doesn't exist
+    // Temporary effects, one per source per player, cleared by the daily reset
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS player_buffs (
+            player_id INTEGER REFERENCES players(id) ON DELETE CASCADE,
+            source TEXT NOT NULL,
+            buff TEXT NOT NULL,
+            amount INTEGER NOT NULL,
+            PRIMARY KEY (player_id, source)
+        )
+        "#).execute(&pool).await?;
+
+    // Create the bounties table if it doesn't exist
+    // Gold is taken from the poster when a bounty is posted and held here until claimed
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS bounties (
+            id SERIAL PRIMARY KEY,
+            target_id INTEGER NOT NULL REFERENCES players(id) ON DELETE CASCADE,
+            poster_id INTEGER REFERENCES players(id) ON DELETE SET NULL,
+            amount INTEGER NOT NULL,
+            posted_at TIMESTAMP NOT NULL DEFAULT NOW(),
+            claimed_by INTEGER REFERENCES players(id) ON DELETE SET NULL,
+            claimed_at TIMESTAMP
+        )
+        "#).execute(&pool).await?;
+    sqlx::query("CREATE INDEX IF NOT EXISTS idx_bounties_open ON bounties (target_id) WHERE claimed_at IS NULL")
+        .execute(&pool).await?;
+
+    // Create the lifetime statistics tables if they don't exist
+    // These are never reset by a new season
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS player_stats (
+            player_id INTEGER PRIMARY KEY REFERENCES players(id) ON DELETE CASCADE,
+            monsters_killed INTEGER NOT NULL DEFAULT 0,
+            deaths INTEGER NOT NULL DEFAULT 0,
+            duels_won INTEGER NOT NULL DEFAULT 0,
+            duels_lost INTEGER NOT NULL DEFAULT 0,
+            gold_earned BIGINT NOT NULL DEFAULT 0,
+            gold_spent BIGINT NOT NULL DEFAULT 0,
+            highest_level INTEGER NOT NULL DEFAULT 1,
+            days_played INTEGER NOT NULL DEFAULT 0,
+            last_played DATE,
+            dragon_kills INTEGER NOT NULL DEFAULT 0
+        )
+        "#).execute(&pool).await?;
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS player_monster_kills (
+            player_id INTEGER REFERENCES players(id) ON DELETE CASCADE,
+            monster TEXT NOT NULL,
+            kills INTEGER NOT NULL DEFAULT 0,
+            PRIMARY KEY (player_id, monster)
+        )
+        "#).execute(&pool).await?;
+
+    // Categorise news and record who it is about, so it can be filtered and grouped by day
+    sqlx::query("ALTER TABLE news ADD COLUMN IF NOT EXISTS kind TEXT NOT NULL DEFAULT 'system'")
+        .execute(&pool).await?;
+    sqlx::query("ALTER TABLE news ADD COLUMN IF NOT EXISTS actor_id INTEGER")
+        .execute(&pool).await?;
+    sqlx::query("ALTER TABLE news ADD COLUMN IF NOT EXISTS target_id INTEGER")
+        .execute(&pool).await?;
+    sqlx::query("ALTER TABLE news ADD COLUMN IF NOT EXISTS game_day DATE")
+        .execute(&pool).await?;
+    sqlx::query("ALTER TABLE news ADD COLUMN IF NOT EXISTS event_id INTEGER REFERENCES events(id) ON DELETE SET NULL")
+        .execute(&pool).await?;
+    // News from before game days were recorded is filed under its calendar date
+    sqlx::query("UPDATE news SET game_day = COALESCE(date, NOW())::DATE WHERE game_day IS NULL")
+        .execute(&pool).await?;
 
     // Create the game_state table if it doesn't exist
     // This table stores global game state variables
@@ -130,15 +598,127 @@ pub async fn init_db_pool() -> Result<PgPool, sqlx::Error> {
         )
         "#).execute(&pool).await?;
 
+    // Create the online_players table if it doesn't exist
+    // Each row is a lock held by a running session, kept fresh by its heartbeat
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS online_players (
+            player_id INTEGER PRIMARY KEY REFERENCES players(id) ON DELETE CASCADE,
+            token TEXT NOT NULL,
+            started_at TIMESTAMP NOT NULL,
+            heartbeat TIMESTAMP NOT NULL,
+            node INTEGER NOT NULL DEFAULT 0,
+            location TEXT NOT NULL DEFAULT ''
+        )
+        "#).execute(&pool).await?;
+    sqlx::query("ALTER TABLE online_players ADD COLUMN IF NOT EXISTS node INTEGER NOT NULL DEFAULT 0")
+        .execute(&pool).await?;
+    sqlx::query("ALTER TABLE online_players ADD COLUMN IF NOT EXISTS location TEXT NOT NULL DEFAULT ''")
+        .execute(&pool).await?;
+
+    // Create the chat_messages table if it doesn't exist
+    // Messages with no recipient are broadcast to every node
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS chat_messages (
+            id SERIAL PRIMARY KEY,
+            sent_at TIMESTAMP NOT NULL DEFAULT NOW(),
+            from_player INTEGER REFERENCES players(id) ON DELETE CASCADE,
+            from_name TEXT NOT NULL,
+            to_player INTEGER REFERENCES players(id) ON DELETE CASCADE,
+            body TEXT NOT NULL
+        )
+        "#).execute(&pool).await?;
+
+    // Create the season tables if they don't exist
+    // A season row with no end date is the season currently being played;
+    // finished seasons keep a snapshot of every player and all of their news
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS seasons (
+            id SERIAL PRIMARY KEY,
+            started_at TIMESTAMP NOT NULL DEFAULT NOW(),
+            ended_at TIMESTAMP
+        )
+        "#).execute(&pool).await?;
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS season_players (
+            season_id INTEGER REFERENCES seasons(id),
+            player_id INTEGER,
+            name TEXT,
+            level INTEGER,
+            exp INTEGER,
+            gold INTEGER,
+            max_hp INTEGER,
+            attack INTEGER,
+            defense INTEGER,
+            dragon_kills INTEGER,
+            spouse TEXT,
+            champion BOOLEAN,
+            PRIMARY KEY (season_id, player_id)
+        )
+        "#).execute(&pool).await?;
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS season_news (
+            id SERIAL PRIMARY KEY,
+            season_id INTEGER REFERENCES seasons(id),
+            date TIMESTAMP,
+            message TEXT
+        )
+        "#).execute(&pool).await?;
+    // Archived news keeps the structure of the live news log
+    sqlx::query("ALTER TABLE season_news ADD COLUMN IF NOT EXISTS kind TEXT NOT NULL DEFAULT 'system'")
+        .execute(&pool).await?;
+    sqlx::query("ALTER TABLE season_news ADD COLUMN IF NOT EXISTS actor_id INTEGER")
+        .execute(&pool).await?;
+    sqlx::query("ALTER TABLE season_news ADD COLUMN IF NOT EXISTS target_id INTEGER")
+        .execute(&pool).await?;
+    sqlx::query("ALTER TABLE season_news ADD COLUMN IF NOT EXISTS game_day DATE")
+        .execute(&pool).await?;
+    sqlx::query("ALTER TABLE season_news ADD COLUMN IF NOT EXISTS event_id INTEGER REFERENCES events(id) ON DELETE SET NULL")
+        .execute(&pool).await?;
+    sqlx::query("INSERT INTO seasons (started_at) SELECT NOW() WHERE NOT EXISTS (SELECT 1 FROM seasons)")
+        .execute(&pool).await?;
+
     // Create indexes to optimize queries for player lookup and leaderboard
     sqlx::query("CREATE INDEX IF NOT EXISTS idx_players_name_lower ON players (LOWER(name))")
         .execute(&pool).await?;
     sqlx::query("CREATE INDEX IF NOT EXISTS idx_players_level_exp ON players (level DESC, exp DESC)")
         .execute(&pool).await?;
+    sqlx::query("CREATE INDEX IF NOT EXISTS idx_players_gold ON players (gold DESC)")
+        .execute(&pool).await?;
+    sqlx::query("CREATE INDEX IF NOT EXISTS idx_players_rating ON players (rating DESC)")
+        .execute(&pool).await?;
+    sqlx::query("CREATE INDEX IF NOT EXISTS idx_players_dragon_kills ON players (dragon_kills DESC, level DESC, exp DESC)")
+        .execute(&pool).await?;
+    // The duel and days-played boards are read from player_stats, so every hero needs a row
+    sqlx::query("INSERT INTO player_stats (player_id) SELECT id FROM players ON CONFLICT (player_id) DO NOTHING")
+        .execute(&pool).await?;
+    sqlx::query("DROP INDEX IF EXISTS idx_player_stats_duels_won")
+        .execute(&pool).await?;
+    sqlx::query("DROP INDEX IF EXISTS idx_player_stats_days_played")
+        .execute(&pool).await?;
+    sqlx::query("CREATE INDEX IF NOT EXISTS idx_player_stats_duels_won_id ON player_stats (duels_won DESC, player_id)")
+        .execute(&pool).await?;
+    sqlx::query("CREATE INDEX IF NOT EXISTS idx_player_stats_days_played_id ON player_stats (days_played DESC, player_id)")
+        .execute(&pool).await?;
+    // Indexes for paging through the news by day and by player
+    sqlx::query("CREATE INDEX IF NOT EXISTS idx_news_game_day ON news (game_day)")
+        .execute(&pool).await?;
+    sqlx::query("CREATE INDEX IF NOT EXISTS idx_news_actor ON news (actor_id)")
+        .execute(&pool).await?;
+    sqlx::query("CREATE INDEX IF NOT EXISTS idx_news_target ON news (target_id)")
+        .execute(&pool).await?;
+    sqlx::query("CREATE INDEX IF NOT EXISTS idx_events_actor ON events (actor_id, kind)")
+        .execute(&pool).await?;
+    sqlx::query("CREATE INDEX IF NOT EXISTS idx_events_target ON events (target_id, kind)")
+        .execute(&pool).await?;
 
     // Initialize the last_reset value if it doesn't exist
     // This is used to track when the daily reset was last performed
-    let today = Local::now().format("%Y-%m-%d").to_string();
+    let today = calendar::game_date(Utc::now()).format("%Y-%m-%d").to_string();
     sqlx::query("INSERT INTO game_state (key, value) VALUES ('last_reset', $1) ON CONFLICT (key) DO NOTHING")
         .bind(today)
         .execute(&pool).await?;
@@ -146,13 +726,20 @@ pub async fn init_db_pool() -> Result<PgPool, sqlx::Error> {
     Ok(pool)
 }
 
-/// Perform daily game reset operations if a new day has started.
+/// Perform daily game reset operations if a new game day has started.
 /// 
 /// This function:  
-/// 1. Checks if the current date is different from the last reset date  
-/// 2. If it is, resets player forest fights, revives dead players, and restores health  
-/// 3. Logs the reset event to the news table  
-/// 4. Updates the last_reset date in the game_state table  
+/// 1. Works out how many game-day boundaries have passed since the last reset  
+/// 2. If any have, resets player forest fights, player fights and play time, revives dead players, and restores health  
+/// 3. Advances the `game_day` counter by every day that passed, so days missed while
+///    nobody played (or the server was down) are still counted  
+/// 4. Logs the reset event to the news table  
+/// 5. Prunes news older than the configured retention period  
+/// 6. Updates the last_reset date in the game_state table  
+/// 
+/// Game days start at the configured reset time and timezone (see `game::calendar`).
+/// The `last_reset` row is locked for the duration, so a scheduled maintenance task
+/// and a player starting a session cannot both perform the same reset.
 /// 
 /// # Parameters
 /// 
@@ -160,35 +747,68 @@ pub async fn init_db_pool() -> Result<PgPool, sqlx::Error> {
 /// 
 /// # Returns
 /// 
-/// `Ok(())` if the reset was successful or not needed, or a `sqlx::Error` if any database operation fails.
-pub async fn daily_reset(pool: &PgPool) -> Result<(), sqlx::Error> {
-    // Get the current date in YYYY-MM-DD format
-    let today = Local::now().format("%Y-%m-%d").to_string();
+/// The number of game days that passed (0 if no reset was needed), or a `sqlx::Error` if any database operation fails.
+pub async fn daily_reset(pool: &PgPool) -> Result<i64, sqlx::Error> {
+    let today = calendar::game_date(Utc::now());
 
     // Retrieve the date of the last reset from the database, using a transaction for consistency
     let mut tx = pool.begin().await?;
-    let last_reset: Option<String> = sqlx::query_scalar("SELECT value FROM game_state WHERE key = 'last_reset'")
+    let last_reset: Option<String> = sqlx::query_scalar("SELECT value FROM game_state WHERE key = 'last_reset' FOR UPDATE")
         .fetch_optional(&mut *tx).await?;
-    // If the last reset was before today (or missing), perform the reset
-    if last_reset.as_deref().unwrap_or("") < today.as_str() {
-        // Reset player forest fights, revive dead players, and restore health
-        sqlx::query("UPDATE players SET forest_fights = $1, alive = TRUE, current_hp = max_hp")
-            .bind(crate::game::MAX_DAILY_FOREST_FIGHTS as i32)
+    // A missing or unreadable date counts as a single day having passed
+    let days = match last_reset.as_deref().map(|d| NaiveDate::parse_from_str(d, "%Y-%m-%d")) {
+        Some(Ok(last)) => (today - last).num_days(),
+        _ => 1,
+    };
+    if days > 0 {
+        // Reset player forest fights, player fights, play time and wagers, revive dead players, and restore health.
+        // Bumping the version makes an online session reload the reset record on its next save.
+        sqlx::query(
+            r#"
+            UPDATE players SET
+                forest_fights = $1, player_fights = $2, alive = TRUE, current_hp = max_hp, poison = 0,
+                time_used = 0, gold_wagered = 0, version = version + 1
+            "#,
+        )
+            .bind(rules().max_daily_forest_fights)
+            .bind(rules().max_daily_player_fights)
             .execute(&mut *tx).await?;
+        // Temporary effects last only until the end of the day
+        sqlx::query("DELETE FROM player_buffs").execute(&mut *tx).await?;
+
+        // Count every game day that passed, not just the one we noticed
+        sqlx::query(
+            r#"
+            INSERT INTO game_state (key, value) VALUES ('game_day', $1::TEXT)
+            ON CONFLICT (key) DO UPDATE SET value = (game_state.value::BIGINT + $1)::TEXT
+            "#,
+        )
+        .bind(days)
+        .execute(&mut *tx).await?;
 
         // Log the reset event to the news table
-        let reset_message = "A new day dawns in the realm. All heroes feel refreshed.";
-        sqlx::query("INSERT INTO news (date, message) VALUES (NOW(), $1)")
-            .bind(reset_message)
-            .execute(&mut *tx).await?;
+        let reset_message = if days == 1 {
+            "A new day dawns in the realm. All heroes feel refreshed.".to_string()
+        } else {
+            format!("{} days have passed in the realm. All heroes feel refreshed.", days)
+        };
+        insert_news(&mut tx, NewsKind::System, None, None, None, &reset_message).await?;
+
+        // Prune old news so the daily news stays readable
+        let retention_days = config::get().news.retention_days;
+        if retention_days > 0 {
+            sqlx::query("DELETE FROM news WHERE game_day < $1")
+                .bind(today - chrono::Days::new(retention_days as u64))
+                .execute(&mut *tx).await?;
+        }
 
         // Update the last_reset date in the game_state table
         sqlx::query("INSERT INTO game_state (key, value) VALUES ('last_reset', $1) ON CONFLICT (key) DO UPDATE SET value = $1")
-            .bind(today)
+            .bind(today.format("%Y-%m-%d").to_string())
             .execute(&mut *tx).await?;
     }
     tx.commit().await?;
-    Ok(())
+    Ok(days.max(0))
 }
 
 /// Create a new player with secure password storage.
@@ -215,7 +835,6 @@ pub async fn daily_reset(pool: &PgPool) -> Result<(), sqlx::Error> {
 /// 
 /// Passwords are hashed using the Argon2 algorithm with a random salt.  
 /// Empty passwords are stored as empty strings to indicate no password is required.
-use sqlx::Row; // Needed for row.get()
 pub async fn create_player(pool: &PgPool, name: &str, password: &str) -> Result<Player, sqlx::Error> {
     let normalized_name = name.trim(); // Force whitespace trim for consistency
 
@@ -226,45 +845,38 @@ pub async fn create_player(pool: &PgPool, name: &str, password: &str) -> Result<
         let argon2 = Argon2::default();
         let password_hash = match argon2.hash_password(password.trim().as_bytes(), &salt) {
             Ok(ph) => ph,
-            Err(e) => return Err(sqlx::Error::Protocol(e.to_string().into())),
+            Err(e) => return Err(sqlx::Error::Protocol(e.to_string())),
         };
         password_hash.to_string()
     };
 
-    let row = sqlx::query(
+    // The hero's statistics row is created with them, so the leaderboards can rank from it
+    let player = sqlx::query_as::<_, Player>(
         r#"
-        INSERT INTO players
-        (name, password, level, exp, gold, current_hp, max_hp, attack, defense,
-         forest_fights, alive, romance, spouse, last_login)
-        VALUES ($1, $2, 1, 0, 100, 20, 20, 5, 2, $3, TRUE, 0, '', NOW())
-        RETURNING *;
+        WITH created AS (
+            INSERT INTO players
+            (name, password, level, exp, gold, current_hp, max_hp, attack, defense,
+             forest_fights, player_fights, alive, romance, spouse, last_login, dragon_kills, rating, created_at)
+            VALUES ($1, $2, 1, 0, $3, $4, $4, $5, $6, $7, $9, TRUE, 0, '', NOW(), 0, $8, NOW())
+            RETURNING *
+        ), stats AS (
+            INSERT INTO player_stats (player_id) SELECT id FROM created
+        )
+        SELECT * FROM created;
         "#,
     )
     .bind(normalized_name)
     .bind(hashed_password)
-    .bind(crate::game::MAX_DAILY_FOREST_FIGHTS as i32)
+    .bind(rules().starting_gold)
+    .bind(rules().starting_hp)
+    .bind(rules().starting_attack)
+    .bind(rules().starting_defense)
+    .bind(rules().max_daily_forest_fights)
+    .bind(rules().starting_rating)
+    .bind(rules().max_daily_player_fights)
     .fetch_one(pool) // Fetch the inserted row
     .await?;
 
-    // Map row into `Player` struct manually
-    let player = Player {
-        id: row.get("id"),
-        name: row.get("name"),
-        password: row.get("password"),
-        level: row.get("level"),
-        exp: row.get("exp"),
-        gold: row.get("gold"),
-        current_hp: row.get("current_hp"),
-        max_hp: row.get("max_hp"),
-        attack: row.get("attack"),
-        defense: row.get("defense"),
-        forest_fights: row.get("forest_fights"),
-        alive: row.get("alive"),
-        romance: row.get("romance"),
-        spouse: row.get("spouse"),
-        last_login: row.try_get::<NaiveDateTime, _>("last_login")?, 
-    };
-
     Ok(player)
 }
 
@@ -327,6 +939,12 @@ pub async fn get_player_by_id(pool: &PgPool, player_id: i32) -> Result<Option<Pl
 /// This function:  
 /// 1. Updates all player fields except the password  
 /// 2. Sets the last_login timestamp to the current time  
+/// 3. Increments the stored version and the in-memory `player.version` on success  
+/// 
+/// The update only applies if the stored version still matches `player.version`,
+/// i.e. nobody else saved this player since it was loaded. Writers that bump the
+/// version include this function, duels, the season rollover and the daily reset,
+/// so a session spanning the reset picks up the new day instead of overwriting it.
 /// 
 /// # Parameters
 /// 
@@ -335,19 +953,41 @@ pub async fn get_player_by_id(pool: &PgPool, player_id: i32) -> Result<Option<Pl
 /// 
 /// # Returns
 /// 
-/// `Ok(())` if the update was successful, or a `sqlx::Error` if the database operation fails.
+/// `Ok(true)` if the update was successful, `Ok(false)` if the stored record was changed
+/// by someone else in the meantime (nothing is written), or a `sqlx::Error` if the database operation fails.
 /// 
 /// # Note
 /// 
 /// This function does not update the password field. To change a password, use a dedicated function that properly hashes the new password.
-pub async fn update_player(pool: &PgPool, player: &Player) -> Result<(), sqlx::Error> {
-    sqlx::query(
+pub async fn update_player(pool: &PgPool, player: &mut Player) -> Result<bool, sqlx::Error> {
+    let mut conn = pool.acquire().await?;
+    let saved = write_player(&mut conn, player).await?;
+    if saved {
+        player.version += 1;
+    }
+    Ok(saved)
+}
+
+/// Write a player's data on an existing connection or transaction.
+/// 
+/// This is the versioned UPDATE behind `update_player`, exposed so that
+/// multi-player operations such as duels can save several players atomically.
+/// Unlike `update_player` it leaves `player.version` alone; callers increment
+/// it once their transaction has committed.
+/// 
+/// # Returns
+/// 
+/// `Ok(true)` if the row was written, `Ok(false)` on a version conflict.
+pub async fn write_player(conn: &mut PgConnection, player: &Player) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(
         r#"
         UPDATE players SET
             level=$1, exp=$2, gold=$3, current_hp=$4, max_hp=$5,
             attack=$6, defense=$7, forest_fights=$8, alive=$9,
-            romance=$10, spouse=$11, last_login=NOW()
-        WHERE id=$12
+            romance=$10, spouse=$11, dragon_kills=$12, time_used=$13, rating=$14, player_fights=$15,
+            gems=$16, gold_wagered=$17, poison=$18, gem_max_hp=$21, gem_attack=$22, gem_defense=$23,
+            last_login=NOW(), version=version + 1
+        WHERE id=$19 AND version=$20
         "#,
     )
     .bind(player.level)
@@ -361,29 +1001,676 @@ pub async fn update_player(pool: &PgPool, player: &Player) -> Result<(), sqlx::E
     .bind(player.alive)
     .bind(player.romance)
     .bind(&player.spouse)
+    .bind(player.dragon_kills)
+    .bind(player.time_used)
+    .bind(player.rating)
+    .bind(player.player_fights)
+    .bind(player.gems)
+    .bind(player.gold_wagered)
+    .bind(player.poison)
     .bind(player.id)
+    .bind(player.version)
+    .bind(player.gem_max_hp)
+    .bind(player.gem_attack)
+    .bind(player.gem_defense)
+    .execute(conn).await?;
+
+    Ok(result.rows_affected() == 1)
+}
+
+/// Claim the online lock for a player, marking them as logged in.
+/// 
+/// The lock is granted if nobody holds it, or if the holder's last heartbeat is
+/// older than `stale_after_secs` (their session crashed or lost its connection).
+/// The session is given the lowest node number not used by another live session,
+/// and starts out in the Town Square.
+/// 
+/// # Parameters
+/// 
+/// * `pool` - The database connection pool  
+/// * `player_id` - The player logging in  
+/// * `token` - A value unique to this session, needed to renew or release the lock  
+/// * `stale_after_secs` - Seconds without a heartbeat after which an existing lock may be taken over  
+/// 
+/// # Returns
+/// 
+/// `Ok(true)` if the lock is now held by this session, `Ok(false)` if the player is
+/// already online elsewhere, or a `sqlx::Error` if the database operation fails.
+pub async fn acquire_session_lock(pool: &PgPool, player_id: i32, token: &str, stale_after_secs: i32) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(
+        r#"
+        INSERT INTO online_players (player_id, token, started_at, heartbeat, node, location)
+        VALUES ($1, $2, NOW(), NOW(), (
+            SELECT MIN(n) FROM generate_series(1, 10000) AS n
+            WHERE n NOT IN (
+                SELECT node FROM online_players
+                WHERE player_id <> $1 AND heartbeat >= NOW() - make_interval(secs => $3)
+            )
+        ), 'Town Square')
+        ON CONFLICT (player_id) DO UPDATE SET
+            token = EXCLUDED.token, started_at = NOW(), heartbeat = NOW(),
+            node = EXCLUDED.node, location = EXCLUDED.location
+        WHERE online_players.heartbeat < NOW() - make_interval(secs => $3)
+        "#,
+    )
+    .bind(player_id)
+    .bind(token)
+    .bind(stale_after_secs as f64)
     .execute(pool).await?;
+    Ok(result.rows_affected() == 1)
+}
+
+/// Renew the online lock held by a session.
+/// 
+/// # Returns
+/// 
+/// `Ok(true)` if the lock is still held by `token`, `Ok(false)` if it was released
+/// or taken over after going stale.
+pub async fn renew_session_lock(pool: &PgPool, player_id: i32, token: &str) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query("UPDATE online_players SET heartbeat = NOW() WHERE player_id = $1 AND token = $2")
+        .bind(player_id)
+        .bind(token)
+        .execute(pool).await?;
+    Ok(result.rows_affected() == 1)
+}
+
+/// Release the online lock held by a session. Does nothing if the lock is held by another session.
+pub async fn release_session_lock(pool: &PgPool, player_id: i32, token: &str) -> Result<(), sqlx::Error> {
+    sqlx::query("DELETE FROM online_players WHERE player_id = $1 AND token = $2")
+        .bind(player_id)
+        .bind(token)
+        .execute(pool).await?;
+    Ok(())
+}
 
+/// Record where in the realm an online player is.
+pub async fn set_player_location(pool: &PgPool, player_id: i32, location: &str) -> Result<(), sqlx::Error> {
+    sqlx::query("UPDATE online_players SET location = $2 WHERE player_id = $1")
+        .bind(player_id)
+        .bind(location)
+        .execute(pool).await?;
     Ok(())
 }
 
-/// Log a game event to the news table.
+/// List every player with a fresh online lock, ordered by node.
+/// 
+/// # Returns
+/// 
+/// A vector of OnlinePlayer structs, or a `sqlx::Error` if the database operation fails.
+pub async fn list_online_players(pool: &PgPool) -> Result<Vec<OnlinePlayer>, sqlx::Error> {
+    sqlx::query_as::<_, OnlinePlayer>(
+        r#"
+        SELECT o.node, o.player_id, p.name, p.level, o.location, o.started_at
+        FROM online_players o JOIN players p ON p.id = o.player_id
+        WHERE o.heartbeat >= NOW() - make_interval(secs => $1)
+        ORDER BY o.node
+        "#,
+    )
+    .bind(config::get().session.lock_timeout_seconds as f64)
+    .fetch_all(pool)
+    .await
+}
+
+/// Send a chat message to one player, or to everyone online if `to_player` is `None`.
+/// 
+/// Listening sessions are woken with a `lord_chat` notification once the message is stored.
 /// 
 /// # Parameters
 /// 
 /// * `pool` - The database connection pool  
-/// * `message` - The event message to log  
+/// * `from` - The player sending the message  
+/// * `to_player` - The recipient's player ID, or `None` to broadcast  
+/// * `body` - The message text  
+pub async fn send_chat_message(pool: &PgPool, from: &Player, to_player: Option<i32>, body: &str) -> Result<(), sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    let id: i32 = sqlx::query_scalar(
+        "INSERT INTO chat_messages (from_player, from_name, to_player, body) VALUES ($1, $2, $3, $4) RETURNING id"
+    )
+    .bind(from.id)
+    .bind(&from.name)
+    .bind(to_player)
+    .bind(body)
+    .fetch_one(&mut *tx).await?;
+    // Notifications are only delivered once the transaction commits
+    sqlx::query("SELECT pg_notify('lord_chat', $1)")
+        .bind(id.to_string())
+        .execute(&mut *tx).await?;
+    tx.commit().await?;
+    Ok(())
+}
+
+/// Retrieve the chat messages for a player that arrived after `after_id`.
+/// 
+/// Includes broadcasts and messages addressed to the player, but not the player's own messages.
+pub async fn get_chat_messages(pool: &PgPool, player_id: i32, after_id: i32) -> Result<Vec<ChatMessage>, sqlx::Error> {
+    sqlx::query_as::<_, ChatMessage>(
+        r#"
+        SELECT id, from_name, to_player IS NULL AS broadcast, body FROM chat_messages
+        WHERE id > $2 AND from_player <> $1 AND (to_player IS NULL OR to_player = $1)
+        ORDER BY id
+        "#,
+    )
+    .bind(player_id)
+    .bind(after_id)
+    .fetch_all(pool)
+    .await
+}
+
+/// The identifier of the newest chat message, or 0 if there are none.
+pub async fn latest_chat_message_id(pool: &PgPool) -> Result<i32, sqlx::Error> {
+    sqlx::query_scalar("SELECT COALESCE(MAX(id), 0) FROM chat_messages")
+        .fetch_one(pool)
+        .await
+}
+
+/// Check whether a player currently holds a fresh online lock.
+pub async fn is_player_online(pool: &PgPool, player_id: i32) -> Result<bool, sqlx::Error> {
+    sqlx::query_scalar(
+        "SELECT EXISTS (SELECT 1 FROM online_players WHERE player_id = $1 AND heartbeat >= NOW() - make_interval(secs => $2))"
+    )
+    .bind(player_id)
+    .bind(config::get().session.lock_timeout_seconds as f64)
+    .fetch_one(pool)
+    .await
+}
+
+/// SQL condition, on a `players` row, that the character is still under new-player protection.
+///
+/// Creation times and the current time both come from the database clock.
+/// `hours_param` is the placeholder bound to `new_player_protection_hours`.
+fn under_protection(hours_param: &str) -> String {
+    format!("COALESCE(created_at > NOW() - make_interval(hours => {}), FALSE)", hours_param)
+}
+
+/// Check whether a player is still within the protection period for new characters.
+pub async fn is_player_protected(pool: &PgPool, player_id: i32) -> Result<bool, sqlx::Error> {
+    let query = format!("SELECT {} FROM players WHERE id = $1", under_protection("$2"));
+    sqlx::query_scalar(&query)
+        .bind(player_id)
+        .bind(rules().new_player_protection_hours)
+        .fetch_optional(pool)
+        .await
+        .map(|protected| protected.unwrap_or(false))
+}
+
+/// Record a temporary effect granted to a player by `source`.
+/// 
+/// # Returns
+/// 
+/// `Ok(true)` if the buff was granted, `Ok(false)` if `source` has already
+/// granted the player a buff since the last daily reset, or a `sqlx::Error`
+/// if the database operation fails.
+pub async fn grant_buff(pool: &PgPool, player_id: i32, source: &str, buff: &str, amount: i32) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(
+        "INSERT INTO player_buffs (player_id, source, buff, amount) VALUES ($1, $2, $3, $4) ON CONFLICT DO NOTHING"
+    )
+    .bind(player_id)
+    .bind(source)
+    .bind(buff)
+    .bind(amount)
+    .execute(pool).await?;
+    Ok(result.rows_affected() == 1)
+}
+
+/// Retrieve a player's active temporary effects as `(source, buff, amount)`.
+pub async fn get_buffs(pool: &PgPool, player_id: i32) -> Result<Vec<(String, String, i32)>, sqlx::Error> {
+    sqlx::query_as::<_, (String, String, i32)>(
+        "SELECT source, buff, amount FROM player_buffs WHERE player_id = $1 ORDER BY source"
+    )
+    .bind(player_id)
+    .fetch_all(pool)
+    .await
+}
+
+/// Retrieve the stacks of items a player carries, by item identifier.
+pub async fn get_inventory(pool: &PgPool, player_id: i32) -> Result<Vec<(String, i32)>, sqlx::Error> {
+    sqlx::query_as::<_, (String, i32)>(
+        "SELECT item_id, quantity FROM inventory WHERE player_id = $1 ORDER BY item_id"
+    )
+    .bind(player_id)
+    .fetch_all(pool)
+    .await
+}
+
+/// Add items to a player's inventory, stacking them with any they already carry.
+pub async fn add_item(pool: &PgPool, player_id: i32, item_id: &str, quantity: i32) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO inventory (player_id, item_id, quantity) VALUES ($1, $2, $3)
+        ON CONFLICT (player_id, item_id) DO UPDATE SET quantity = inventory.quantity + EXCLUDED.quantity
+        "#,
+    )
+    .bind(player_id)
+    .bind(item_id)
+    .bind(quantity)
+    .execute(pool).await?;
+    Ok(())
+}
+
+/// Take items out of a player's inventory as part of the caller's transaction,
+/// removing the stack once it is empty.
+/// 
+/// The caller is responsible for saving whatever the items did to the player in
+/// the same transaction.
+/// 
+/// # Returns
+/// 
+/// `Ok(true)` if the player had enough of the item, `Ok(false)` if not (nothing is
+/// removed), or a `sqlx::Error` if the database operation fails.
+pub async fn remove_item(conn: &mut PgConnection, player_id: i32, item_id: &str, quantity: i32) -> Result<bool, sqlx::Error> {
+    let left: Option<i32> = sqlx::query_scalar(
+        "SELECT quantity FROM inventory WHERE player_id = $1 AND item_id = $2 FOR UPDATE"
+    )
+    .bind(player_id)
+    .bind(item_id)
+    .fetch_optional(&mut *conn).await?;
+    match left {
+        Some(left) if left > quantity => {
+            sqlx::query("UPDATE inventory SET quantity = quantity - $3 WHERE player_id = $1 AND item_id = $2")
+                .bind(player_id)
+                .bind(item_id)
+                .bind(quantity)
+                .execute(&mut *conn).await?;
+        }
+        Some(left) if left == quantity => {
+            sqlx::query("DELETE FROM inventory WHERE player_id = $1 AND item_id = $2")
+                .bind(player_id)
+                .bind(item_id)
+                .execute(&mut *conn).await?;
+        }
+        _ => return Ok(false),
+    }
+    Ok(true)
+}
+
+/// Record a bounty on a hero's head as part of the caller's transaction.
+/// 
+/// The caller is responsible for taking `amount` gold from the poster in the same transaction.
+pub async fn insert_bounty(conn: &mut PgConnection, poster_id: i32, target_id: i32, amount: i32) -> Result<(), sqlx::Error> {
+    sqlx::query("INSERT INTO bounties (target_id, poster_id, amount) VALUES ($1, $2, $3)")
+        .bind(target_id)
+        .bind(poster_id)
+        .bind(amount)
+        .execute(conn).await?;
+    Ok(())
+}
+
+/// Claim every open bounty on `target_id` for `winner_id`, as part of the caller's transaction.
+/// 
+/// Bounties the winner posted themselves stay open. The caller is responsible
+/// for giving the returned gold to the winner in the same transaction.
+/// 
+/// # Returns
+/// 
+/// The total gold claimed, or a `sqlx::Error` if the database operation fails.
+pub async fn claim_bounties(conn: &mut PgConnection, target_id: i32, winner_id: i32) -> Result<i32, sqlx::Error> {
+    let amounts: Vec<i32> = sqlx::query_scalar(
+        r#"
+        UPDATE bounties SET claimed_by = $2, claimed_at = NOW()
+        WHERE target_id = $1 AND claimed_at IS NULL AND poster_id IS DISTINCT FROM $2
+        RETURNING amount
+        "#,
+    )
+    .bind(target_id)
+    .bind(winner_id)
+    .fetch_all(conn).await?;
+    Ok(amounts.into_iter().fold(0i32, i32::saturating_add))
+}
+
+/// List the heroes with open bounties on their heads, richest bounty first.
+pub async fn list_bounties(pool: &PgPool) -> Result<Vec<BountyTotal>, sqlx::Error> {
+    sqlx::query_as::<_, BountyTotal>(
+        r#"
+        SELECT p.id AS target_id, p.name AS target_name, p.level AS target_level,
+            SUM(b.amount)::BIGINT AS total, COUNT(*) AS posted
+        FROM bounties b JOIN players p ON p.id = b.target_id
+        WHERE b.claimed_at IS NULL
+        GROUP BY p.id, p.name, p.level
+        ORDER BY total DESC, p.name
+        "#,
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// Close the current season and start a new one.
+/// 
+/// This function:  
+/// 1. Snapshots every player into `season_players`, flagging the champion(s)  
+/// 2. Moves the news log into `season_news`  
+/// 3. Wipes every character back to the stats of a freshly created hero, voiding open bounties  
+/// 4. Marks the season as ended, opens the next one and announces it in the news  
+/// 
+/// Champions are the players with the most dragon kills, ties broken by level
+/// and then experience. Players who tie on all three share the title.
+/// Accounts (names and passwords) survive the rollover.
+/// 
+/// # Parameters
+/// 
+/// * `pool` - The database connection pool
+/// 
+/// # Returns
+/// 
+/// A `SeasonSummary` describing the finished season, or a `sqlx::Error` if any database operation fails.
+/// All steps run in one transaction, so a failure leaves the current season untouched.
+pub async fn end_season(pool: &PgPool) -> Result<SeasonSummary, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    // Lock the running season so two rollovers cannot race each other
+    let (season_id, started_at): (i32, NaiveDateTime) = sqlx::query_as(
+        "SELECT id, started_at FROM seasons WHERE ended_at IS NULL ORDER BY id DESC LIMIT 1 FOR UPDATE"
+    )
+    .fetch_one(&mut *tx).await?;
+
+    // Archive every player, flagging whoever ties with the best record
+    let archived_players = sqlx::query(
+        r#"
+        INSERT INTO season_players
+        (season_id, player_id, name, level, exp, gold, max_hp, attack, defense,
+         dragon_kills, spouse, champion)
+        SELECT $1, id, name, level, exp, gold, max_hp, attack, defense, dragon_kills, spouse,
+            (dragon_kills, level, exp) = (
+                SELECT dragon_kills, level, exp FROM players
+                ORDER BY dragon_kills DESC, level DESC, exp DESC LIMIT 1
+            )
+        FROM players
+        "#,
+    )
+    .bind(season_id)
+    .execute(&mut *tx).await?
+    .rows_affected();
+
+    let champions: Vec<String> = sqlx::query_scalar(
+        "SELECT name FROM season_players WHERE season_id = $1 AND champion ORDER BY name"
+    )
+    .bind(season_id)
+    .fetch_all(&mut *tx).await?;
+
+    // Move the news log into the archive
+    let archived_news = sqlx::query(
+        r#"
+        INSERT INTO season_news (season_id, date, game_day, kind, event_id, actor_id, target_id, message)
+        SELECT $1, date, game_day, kind, event_id, actor_id, target_id, message FROM news ORDER BY id
+        "#,
+    )
+    .bind(season_id)
+    .execute(&mut *tx).await?
+    .rows_affected();
+    sqlx::query("DELETE FROM news").execute(&mut *tx).await?;
+
+    // Bounties are paid in this season's gold, which is about to be wiped
+    sqlx::query("DELETE FROM bounties WHERE claimed_at IS NULL").execute(&mut *tx).await?;
+    // Items found this season would give a head start in the next
+    sqlx::query("DELETE FROM inventory").execute(&mut *tx).await?;
+    sqlx::query("DELETE FROM player_buffs").execute(&mut *tx).await?;
+
+    // Wipe every character back to creation defaults
+    sqlx::query(
+        r#"
+        UPDATE players SET
+            level=1, exp=0, gold=$1, current_hp=$2, max_hp=$2, attack=$3, defense=$4,
+            forest_fights=$5, player_fights=$7, alive=TRUE, romance=0, spouse='', dragon_kills=0, rating=$6,
+            gems=0, gem_max_hp=0, gem_attack=0, gem_defense=0, gold_wagered=0, poison=0,
+            version=version + 1
+        "#,
+    )
+    .bind(rules().starting_gold)
+    .bind(rules().starting_hp)
+    .bind(rules().starting_attack)
+    .bind(rules().starting_defense)
+    .bind(rules().max_daily_forest_fights)
+    .bind(rules().starting_rating)
+    .bind(rules().max_daily_player_fights)
+    .execute(&mut *tx).await?;
+
+    // Close the finished season and open the next one
+    sqlx::query("UPDATE seasons SET ended_at = NOW() WHERE id = $1")
+        .bind(season_id)
+        .execute(&mut *tx).await?;
+    sqlx::query("INSERT INTO seasons (started_at) VALUES (NOW())")
+        .execute(&mut *tx).await?;
+
+    let announcement = if champions.is_empty() {
+        format!("Season {} has ended without a champion. A new season begins!", season_id)
+    } else {
+        format!("Season {} has ended! All hail {}. A new season begins!", season_id, champions.join(" and "))
+    };
+    insert_news(&mut tx, NewsKind::System, None, None, None, &announcement).await?;
+
+    tx.commit().await?;
+    Ok(SeasonSummary { season_id, started_at, champions, archived_players, archived_news })
+}
+
+/// Log a game event to the events table, and to the news if it is newsworthy.
+/// 
+/// Online sessions are woken with a `lord_news` notification once the news is stored,
+/// so they can show it as breaking news.
+/// 
+/// # Parameters
+/// 
+/// * `pool` - The database connection pool  
+/// * `event` - The event to record  
 /// 
 /// # Returns
 /// 
 /// `Ok(())` if the event was logged successfully, or a `sqlx::Error` if the database operation fails.
-pub async fn log_event(pool: &PgPool, message: &str) -> Result<(), sqlx::Error> {
-    sqlx::query("INSERT INTO news (date, message) VALUES (NOW(), $1)")
-        .bind(message)
-        .execute(pool).await?;
+pub async fn log_event(pool: &PgPool, event: &GameEvent) -> Result<(), sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    insert_event(&mut tx, event).await?;
+    tx.commit().await?;
+    Ok(())
+}
+
+/// Record a game event and its news line as part of the caller's transaction.
+/// 
+/// Used where the event must be saved together with its outcome, such as a duel.
+pub async fn insert_event(conn: &mut PgConnection, event: &GameEvent) -> Result<(), sqlx::Error> {
+    let event_id: i32 = sqlx::query_scalar(
+        r#"
+        INSERT INTO events (game_day, season_id, kind, actor_id, target_id, gold, exp, payload)
+        VALUES ($1, (SELECT MAX(id) FROM seasons WHERE ended_at IS NULL), $2, $3, $4, $5, $6, $7)
+        RETURNING id
+        "#,
+    )
+    .bind(calendar::game_date(Utc::now()))
+    .bind(event.kind.as_str())
+    .bind(event.actor_id)
+    .bind(event.target_id)
+    .bind(event.gold)
+    .bind(event.exp)
+    .bind(Json(&event.payload))
+    .fetch_one(&mut *conn).await?;
+    for (player_id, delta) in event.stats_changes() {
+        add_player_stats(conn, player_id, &delta).await?;
+    }
+    if let Some(message) = event.news_text() {
+        insert_news(conn, event.kind.news_kind(), Some(event_id), event.actor_id, event.target_id, &message).await?;
+    }
+    Ok(())
+}
+
+/// Apply a change to a player's lifetime statistics, creating their record if needed.
+async fn add_player_stats(conn: &mut PgConnection, player_id: i32, delta: &StatsDelta) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO player_stats
+        (player_id, monsters_killed, deaths, duels_won, duels_lost, gold_earned, gold_spent, highest_level, dragon_kills)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, GREATEST($8, 1), $9)
+        ON CONFLICT (player_id) DO UPDATE SET
+            monsters_killed = player_stats.monsters_killed + EXCLUDED.monsters_killed,
+            deaths = player_stats.deaths + EXCLUDED.deaths,
+            duels_won = player_stats.duels_won + EXCLUDED.duels_won,
+            duels_lost = player_stats.duels_lost + EXCLUDED.duels_lost,
+            gold_earned = player_stats.gold_earned + EXCLUDED.gold_earned,
+            gold_spent = player_stats.gold_spent + EXCLUDED.gold_spent,
+            highest_level = GREATEST(player_stats.highest_level, EXCLUDED.highest_level),
+            dragon_kills = player_stats.dragon_kills + EXCLUDED.dragon_kills
+        "#,
+    )
+    .bind(player_id)
+    .bind(i32::from(delta.monster.is_some()))
+    .bind(delta.deaths)
+    .bind(delta.duels_won)
+    .bind(delta.duels_lost)
+    .bind(delta.gold_earned)
+    .bind(delta.gold_spent)
+    .bind(delta.highest_level)
+    .bind(delta.dragon_kills)
+    .execute(&mut *conn).await?;
+    if let Some(monster) = &delta.monster {
+        sqlx::query(
+            r#"
+            INSERT INTO player_monster_kills (player_id, monster, kills) VALUES ($1, $2, 1)
+            ON CONFLICT (player_id, monster) DO UPDATE SET kills = player_monster_kills.kills + 1
+            "#,
+        )
+        .bind(player_id)
+        .bind(monster)
+        .execute(&mut *conn).await?;
+    }
+    Ok(())
+}
+
+/// Count today as a day played for the player, if it is not counted already.
+pub async fn record_day_played(pool: &PgPool, player_id: i32) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO player_stats (player_id, days_played, last_played) VALUES ($1, 1, $2)
+        ON CONFLICT (player_id) DO UPDATE SET days_played = player_stats.days_played + 1, last_played = $2
+        WHERE player_stats.last_played IS DISTINCT FROM $2
+        "#,
+    )
+    .bind(player_id)
+    .bind(calendar::game_date(Utc::now()))
+    .execute(pool).await?;
+    Ok(())
+}
+
+/// Retrieve a player's lifetime statistics.
+/// 
+/// Players with no recorded events yet get zeroes; the highest level is never
+/// reported below the player's current level.
+pub async fn get_player_stats(pool: &PgPool, player_id: i32) -> Result<PlayerStats, sqlx::Error> {
+    sqlx::query_as::<_, PlayerStats>(
+        r#"
+        SELECT
+            COALESCE(s.monsters_killed, 0) AS monsters_killed,
+            COALESCE(s.deaths, 0) AS deaths,
+            COALESCE(s.duels_won, 0) AS duels_won,
+            COALESCE(s.duels_lost, 0) AS duels_lost,
+            COALESCE(s.gold_earned, 0) AS gold_earned,
+            COALESCE(s.gold_spent, 0) AS gold_spent,
+            GREATEST(COALESCE(s.highest_level, 1), p.level) AS highest_level,
+            COALESCE(s.days_played, 0) AS days_played,
+            GREATEST(COALESCE(s.dragon_kills, 0), p.dragon_kills) AS dragon_kills
+        FROM players p LEFT JOIN player_stats s ON s.player_id = p.id
+        WHERE p.id = $1
+        "#,
+    )
+    .bind(player_id)
+    .fetch_one(pool)
+    .await
+}
+
+/// Retrieve how many of each monster a player has slain, most slain first.
+pub async fn get_monster_kills(pool: &PgPool, player_id: i32) -> Result<Vec<(String, i32)>, sqlx::Error> {
+    sqlx::query_as::<_, (String, i32)>(
+        "SELECT monster, kills FROM player_monster_kills WHERE player_id = $1 ORDER BY kills DESC, monster"
+    )
+    .bind(player_id)
+    .fetch_all(pool)
+    .await
+}
+
+/// Insert a news entry for the current game day and announce it on `lord_news`.
+/// 
+/// The notification is only delivered once the caller's transaction commits.
+async fn insert_news(conn: &mut PgConnection, kind: NewsKind, event_id: Option<i32>, actor_id: Option<i32>, target_id: Option<i32>, message: &str) -> Result<(), sqlx::Error> {
+    let id: i32 = sqlx::query_scalar(
+        "INSERT INTO news (date, game_day, kind, event_id, actor_id, target_id, message) VALUES (NOW(), $1, $2, $3, $4, $5, $6) RETURNING id"
+    )
+    .bind(calendar::game_date(Utc::now()))
+    .bind(kind.as_str())
+    .bind(event_id)
+    .bind(actor_id)
+    .bind(target_id)
+    .bind(message)
+    .fetch_one(&mut *conn).await?;
+    sqlx::query("SELECT pg_notify('lord_news', $1)")
+        .bind(id.to_string())
+        .execute(&mut *conn).await?;
     Ok(())
 }
 
+/// Retrieve the news entries logged after `after_id`, oldest first.
+pub async fn get_news_since(pool: &PgPool, after_id: i32) -> Result<Vec<NewsEntry>, sqlx::Error> {
+    sqlx::query_as::<_, NewsEntry>(
+        r#"
+        SELECT id, date, game_day, kind, actor_id, target_id, COALESCE(message, '') AS message
+        FROM news WHERE id > $1 ORDER BY id
+        "#,
+    )
+    .bind(after_id)
+    .fetch_all(pool)
+    .await
+}
+
+/// Retrieve one page of the news matching `filter`.
+/// 
+/// Pages count back from the newest entry: page 0 holds the latest `page_size`
+/// entries, page 1 the ones before those, and so on.
+/// 
+/// # Parameters
+/// 
+/// * `pool` - The database connection pool  
+/// * `filter` - Which categories and players to include  
+/// * `page` - The page number, 0 being the most recent  
+/// * `page_size` - The number of entries per page  
+/// 
+/// # Returns
+/// 
+/// The entries on the page in chronological order (oldest first), or a `sqlx::Error` if the database operation fails.
+pub async fn get_news_page(pool: &PgPool, filter: &NewsFilter, page: i64, page_size: i64) -> Result<Vec<NewsEntry>, sqlx::Error> {
+    let entries = sqlx::query_as::<_, NewsEntry>(
+        r#"
+        SELECT id, date, game_day, kind, actor_id, target_id, COALESCE(message, '') AS message
+        FROM news
+        WHERE ($1::TEXT IS NULL OR kind = $1)
+          AND ($2::INTEGER IS NULL OR actor_id = $2 OR target_id = $2)
+        ORDER BY id DESC LIMIT $3 OFFSET $4
+        "#,
+    )
+    .bind(filter.kind.map(NewsKind::as_str))
+    .bind(filter.involving)
+    .bind(page_size)
+    .bind(page * page_size)
+    .fetch_all(pool)
+    .await?;
+    Ok(entries.into_iter().rev().collect())
+}
+
+/// Count the news entries matching `filter`, for paging.
+pub async fn count_news(pool: &PgPool, filter: &NewsFilter) -> Result<i64, sqlx::Error> {
+    sqlx::query_scalar(
+        r#"
+        SELECT COUNT(*) FROM news
+        WHERE ($1::TEXT IS NULL OR kind = $1)
+          AND ($2::INTEGER IS NULL OR actor_id = $2 OR target_id = $2)
+        "#,
+    )
+    .bind(filter.kind.map(NewsKind::as_str))
+    .bind(filter.involving)
+    .fetch_one(pool)
+    .await
+}
+
+/// The identifier of the newest news entry, or 0 if there are none.
+pub async fn latest_news_id(pool: &PgPool) -> Result<i32, sqlx::Error> {
+    sqlx::query_scalar("SELECT COALESCE(MAX(id), 0) FROM news")
+        .fetch_one(pool)
+        .await
+}
+
 /// Retrieve recent game events from the news table.
 /// 
 /// This function:  
@@ -417,9 +1704,12 @@ pub async fn get_latest_events(pool: &PgPool, limit: i64) -> Result<Vec<(NaiveDa
     Ok(events.into_iter().map(|e| (e.date, e.message)).rev().collect())
 }
 
-/// List all alive players, excluding the specified player.
+/// List all alive players, excluding the specified player, anyone currently online,
+/// and new characters still under protection.
 /// 
-/// This function is typically used to show potential PvP targets.
+/// This function is typically used to show potential PvP targets. Online players
+/// are left out because their session would overwrite the duel's outcome; new
+/// characters are protected for `new_player_protection_hours` after creation.
 /// 
 /// # Parameters
 /// 
@@ -430,31 +1720,63 @@ pub async fn get_latest_events(pool: &PgPool, limit: i64) -> Result<Vec<(NaiveDa
 /// 
 /// A vector of PlayerInfo structs for all alive players except the excluded one, or a `sqlx::Error` if the database operation fails.
 pub async fn list_alive_players(pool: &PgPool, exclude_id: i32) -> Result<Vec<PlayerInfo>, sqlx::Error> {
-    sqlx::query_as::<_, PlayerInfo>(
-        "SELECT id, name, level FROM players WHERE alive = true AND id != $1 ORDER BY name"
-    )
+    let query = format!(
+        "SELECT id, name, level FROM players WHERE alive = true AND id != $1
+         AND id NOT IN (SELECT player_id FROM online_players WHERE heartbeat >= NOW() - make_interval(secs => $2))
+         AND NOT {}
+         ORDER BY name",
+        under_protection("$3")
+    );
+    sqlx::query_as::<_, PlayerInfo>(&query)
     .bind(exclude_id)
+    .bind(config::get().session.lock_timeout_seconds as f64)
+    .bind(rules().new_player_protection_hours)
     .fetch_all(pool)
     .await
 }
 
-/// Retrieve the top players ranked by level and experience.
+/// Retrieve one page of a leaderboard, covering every player.
 /// 
 /// # Parameters
 /// 
 /// * `pool` - The database connection pool  
-/// * `limit` - The maximum number of players to retrieve  
+/// * `board` - Which ranking to use  
+/// * `page` - The page number, 0 being the top of the board  
+/// * `page_size` - The number of players per page  
 /// 
 /// # Returns
 /// 
-/// A vector of PlayerInfo structs for the top players, or a `sqlx::Error` if the database operation fails.
-pub async fn get_top_players(pool: &PgPool, limit: i64) -> Result<Vec<PlayerInfo>, sqlx::Error> {
-    sqlx::query_as::<_, PlayerInfo>(
-        "SELECT id, name, level FROM players ORDER BY level DESC, exp DESC LIMIT $1"
-    )
-    .bind(limit)
-    .fetch_all(pool)
-    .await
+/// The players on the page in rank order, or a `sqlx::Error` if the database operation fails.
+pub async fn get_leaderboard(pool: &PgPool, board: Leaderboard, page: i64, page_size: i64) -> Result<Vec<LeaderboardEntry>, sqlx::Error> {
+    // The board only selects from fixed column expressions, never from user input
+    let query = format!(
+        r#"
+        SELECT p.id, p.name, p.level, COALESCE(p.alive, FALSE) AS alive,
+            EXISTS (
+                SELECT 1 FROM online_players o
+                WHERE o.player_id = p.id AND o.heartbeat >= NOW() - make_interval(secs => $3)
+            ) AS online,
+            {} AS score
+        FROM {}
+        ORDER BY {} LIMIT $1 OFFSET $2
+        "#,
+        board.score_sql(),
+        board.source_sql(),
+        board.order_sql()
+    );
+    sqlx::query_as::<_, LeaderboardEntry>(&query)
+        .bind(page_size)
+        .bind(page * page_size)
+        .bind(config::get().session.lock_timeout_seconds as f64)
+        .fetch_all(pool)
+        .await
+}
+
+/// Count every player, for paging through the leaderboards.
+pub async fn count_players(pool: &PgPool) -> Result<i64, sqlx::Error> {
+    sqlx::query_scalar("SELECT COUNT(*) FROM players")
+        .fetch_one(pool)
+        .await
 }
 
 /// Verify a password against a stored hash using Argon2.
@@ -495,3 +1817,61 @@ pub fn verify_password(entered_pw: &str, hashed: &str) -> bool {
         Err(_) => false,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::Connection;
+
+    /// A fresh game database for the named test, or `None` if no test database is configured.
+    ///
+    /// Like the session tests, this needs `TEST_DATABASE_URL` to point at a
+    /// server it may create databases on.
+    async fn test_pool(name: &str) -> Option<PgPool> {
+        let Ok(admin_url) = env::var("TEST_DATABASE_URL") else {
+            eprintln!("TEST_DATABASE_URL is not set; skipping the {} database test", name);
+            return None;
+        };
+        let database = format!("lord_test_{}", name);
+        let mut admin = PgConnection::connect(&admin_url).await.expect("connect to TEST_DATABASE_URL");
+        sqlx::query(&format!("DROP DATABASE IF EXISTS {} WITH (FORCE)", database))
+            .execute(&mut admin)
+            .await
+            .expect("drop the old test database");
+        sqlx::query(&format!("CREATE DATABASE {}", database))
+            .execute(&mut admin)
+            .await
+            .expect("create the test database");
+        let (server, _) = admin_url.rsplit_once('/').expect("TEST_DATABASE_URL names a database");
+        Some(connect_and_migrate(&format!("{}/{}", server, database)).await.expect("set up the test database"))
+    }
+
+    #[tokio::test]
+    async fn a_session_spanning_the_daily_reset_reloads_the_reset_record() {
+        let Some(pool) = test_pool("daily_reset").await else { return };
+        let created = create_player(&pool, "Gareth", "").await.expect("create a hero");
+        // The hero logs in and plays through the day
+        let mut session = get_player_by_id(&pool, created.id).await.expect("load the hero").expect("the hero exists");
+        session.forest_fights = 0;
+        session.current_hp = 1;
+        session.poison = 3;
+        session.time_used = 600;
+
+        // The day turns over while they are still online
+        let yesterday = calendar::game_date(Utc::now()) - chrono::Days::new(1);
+        sqlx::query("INSERT INTO game_state (key, value) VALUES ('last_reset', $1) ON CONFLICT (key) DO UPDATE SET value = $1")
+            .bind(yesterday.format("%Y-%m-%d").to_string())
+            .execute(&pool)
+            .await
+            .expect("backdate the last reset");
+        assert_eq!(daily_reset(&pool).await.expect("run the daily reset"), 1);
+
+        // The stale session can no longer overwrite the reset...
+        assert!(!update_player(&pool, &mut session.clone()).await.expect("try to save"));
+        // ...and saving picks up the new day instead
+        assert!(!crate::game::save_player(&pool, &mut session).await);
+        assert_eq!(session.forest_fights, rules().max_daily_forest_fights);
+        assert_eq!((session.current_hp, session.poison, session.time_used), (session.max_hp, 0, 0));
+        assert!(update_player(&pool, &mut session).await.expect("save the reloaded hero"));
+    }
+}
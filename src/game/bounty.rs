@@ -0,0 +1,105 @@
+//! Bounties: gold posted on a hero's head, paid to whoever defeats them in a duel.
+//!
+//! The poster's gold is taken when the bounty is posted and held in escrow in
+//! the `bounties` table. When the target loses a duel, `pvp::challenge_player`
+//! pays every open bounty on them to the winner in the same transaction as the
+//! duel itself. Posting and claiming are both announced in the news.
+use colored::Colorize;
+use sqlx::PgPool;
+use crate::config::rules;
+use crate::db;
+use crate::game::{GameEvent, Player};
+use crate::ui;
+
+/// Show the bounty board and offer to post a new bounty.
+pub async fn visit_bounty_board(conn: &PgPool, player: &mut Player) {
+    println!("\n{}", "Bounty Board".bright_red().bold());
+    match db::list_bounties(conn).await {
+        Ok(list) if list.is_empty() => println!("The board is empty. Nobody has a price on their head."),
+        Ok(list) => {
+            println!("{:<22}{:<7}{:<10}Posted by", "Wanted", "Level", "Reward");
+            for bounty in list {
+                println!(
+                    "{:<22}{:<7}{:<10}{} hero(es)",
+                    bounty.target_name, bounty.target_level, bounty.total, bounty.posted
+                );
+            }
+        }
+        Err(e) => {
+            println!("Could not read the bounty board: {}", e);
+            return;
+        }
+    }
+
+    let choice = ui::prompt("\nPost a bounty of your own? (Y/N): ");
+    if choice.trim().eq_ignore_ascii_case("Y") {
+        post_bounty(conn, player).await;
+    }
+}
+
+/// Ask for a target and an amount, then post the bounty.
+async fn post_bounty(conn: &PgPool, player: &mut Player) {
+    let name = ui::prompt("Whose head do you want? ");
+    let target = match db::get_player_by_name(conn, name.trim()).await {
+        Ok(Some(target)) => target,
+        Ok(None) => {
+            println!("No hero by that name lives in the realm.");
+            return;
+        }
+        Err(e) => {
+            println!("Error retrieving player: {}", e);
+            return;
+        }
+    };
+    if target.id == player.id {
+        println!("The clerk raises an eyebrow. You cannot post a bounty on yourself.");
+        return;
+    }
+
+    let min = rules().min_bounty;
+    let input = ui::prompt(&format!("How much gold? (at least {}, you have {}): ", min, player.gold));
+    let amount = match input.trim().parse::<i32>() {
+        Ok(amount) if amount >= min => amount,
+        Ok(_) => {
+            println!("The guild does not bother with bounties under {} gold.", min);
+            return;
+        }
+        Err(_) => {
+            println!("Invalid amount.");
+            return;
+        }
+    };
+    if amount > player.gold {
+        println!("You don't have that much gold.");
+        return;
+    }
+
+    // The gold leaves the poster's purse in the same transaction that records the bounty
+    let before = player.clone();
+    player.gold -= amount;
+    let saved = async {
+        let mut tx = conn.begin().await?;
+        if !db::write_player(&mut tx, player).await? {
+            tx.rollback().await?;
+            return Ok(false);
+        }
+        db::insert_bounty(&mut tx, player.id, target.id, amount).await?;
+        db::insert_event(&mut tx, &GameEvent::bounty_posted(player, &target, amount)).await?;
+        tx.commit().await?;
+        Ok::<bool, sqlx::Error>(true)
+    }.await;
+    match saved {
+        Ok(true) => {
+            player.version += 1;
+            println!("{}", format!("A bounty of {} gold is posted on {}'s head.", amount, target.name).bright_yellow());
+        }
+        Ok(false) => {
+            println!("Your purse has changed since you arrived. The clerk asks you to try again.");
+            *player = before;
+        }
+        Err(e) => {
+            println!("The bounty could not be posted: {}", e);
+            *player = before;
+        }
+    }
+}
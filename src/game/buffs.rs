@@ -0,0 +1,132 @@
+//! Temporary effects that last until the next daily reset.
+//!
+//! The bard's songs and a married hero's time with Violet grant buffs: extra
+//! forest fights, extra strength in combat, or extra charm when flirting. Each
+//! source grants at most one buff per game day; buffs are kept in the
+//! `player_buffs` table and all of them are cleared by the daily reset.
+use colored::Colorize;
+use rand::Rng;
+use sqlx::PgPool;
+use crate::config::rules;
+use crate::db;
+use crate::game::rng::GameRng;
+use crate::game::{Player, save_player};
+
+/// A kind of temporary effect.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Buff {
+    /// Extra forest fights today (granted at once; kept for the character sheet)
+    ForestFights,
+    /// Extra attack in every fight until the reset
+    Strength,
+    /// Extra romance points each time the hero flirts
+    Charm,
+}
+
+impl Buff {
+    /// Every kind of buff.
+    const ALL: [Buff; 3] = [Buff::ForestFights, Buff::Strength, Buff::Charm];
+
+    /// The value stored in the `buff` column.
+    fn as_str(self) -> &'static str {
+        match self {
+            Buff::ForestFights => "forest_fights",
+            Buff::Strength => "strength",
+            Buff::Charm => "charm",
+        }
+    }
+
+    /// Parse a stored `buff`.
+    fn parse(buff: &str) -> Option<Buff> {
+        Buff::ALL.into_iter().find(|b| b.as_str() == buff)
+    }
+
+    /// How the effect is shown on the character sheet.
+    fn describe(self, amount: i32) -> String {
+        match self {
+            Buff::ForestFights => format!("+{} forest fights today", amount),
+            Buff::Strength => format!("+{} strength in combat", amount),
+            Buff::Charm => format!("+{} charm", amount),
+        }
+    }
+}
+
+/// Grant a buff from `source`, unless that source has already granted one today.
+///
+/// Extra forest fights are added to the player at once and the player is saved.
+///
+/// # Returns
+///
+/// `true` if the buff was granted.
+async fn grant(conn: &PgPool, player: &mut Player, source: &str, buff: Buff, amount: i32) -> bool {
+    match db::grant_buff(conn, player.id, source, buff.as_str(), amount).await {
+        Ok(true) => {
+            if buff == Buff::ForestFights {
+                player.forest_fights += amount;
+                save_player(conn, player).await;
+            }
+            true
+        }
+        Ok(false) => false,
+        Err(e) => {
+            println!("Something distracts you. (Error: {})", e);
+            false
+        }
+    }
+}
+
+/// The total amount of a buff the player enjoys right now.
+pub async fn amount(conn: &PgPool, player_id: i32, buff: Buff) -> i32 {
+    match db::get_buffs(conn, player_id).await {
+        Ok(buffs) => buffs
+            .iter()
+            .filter(|(_, name, _)| Buff::parse(name) == Some(buff))
+            .map(|(_, _, amount)| amount)
+            .sum(),
+        Err(_) => 0,
+    }
+}
+
+/// List the player's active buffs on the character sheet.
+pub async fn show_active(conn: &PgPool, player_id: i32) {
+    let buffs = db::get_buffs(conn, player_id).await.unwrap_or_default();
+    let lines: Vec<String> = buffs
+        .iter()
+        .filter_map(|(source, name, amount)| {
+            Buff::parse(name).map(|buff| format!("  {} (from {})", buff.describe(*amount), source))
+        })
+        .collect();
+    if lines.is_empty() {
+        println!("Active effects: (none)");
+    } else {
+        println!("Active effects (until the daily reset):");
+        for line in lines {
+            println!("{}", line.bright_cyan());
+        }
+    }
+}
+
+/// Listen to the bard, whose song grants a random buff once per day.
+pub async fn listen_to_bard(conn: &PgPool, player: &mut Player, rng: &mut GameRng) {
+    let (song, buff, amount) = match rng.random_range(0..3) {
+        0 => ("a rousing song of the hunt", Buff::ForestFights, rules().bard_forest_fights),
+        1 => ("a ballad of ancient heroes", Buff::Strength, rules().buff_strength),
+        _ => ("a sweet song of courtly love", Buff::Charm, 1),
+    };
+    if !grant(conn, player, "the bard", buff, amount).await {
+        println!("The bard smiles. \"You've heard my best today, friend. Come back tomorrow.\"");
+        return;
+    }
+    println!("The bard strums his lute and sings {}.", song);
+    println!("{}", format!("You feel inspired: {}!", buff.describe(amount)).bright_green());
+}
+
+/// Spend time with Violet, who sends her spouse off stronger once per day.
+pub async fn time_with_violet(conn: &PgPool, player: &mut Player) {
+    if grant(conn, player, "Violet", Buff::Strength, rules().buff_strength).await {
+        println!("Violet kisses you and sends you off with a packed lunch and a lucky charm.");
+        println!("{}", format!("You feel ready for anything: {}!", Buff::Strength.describe(rules().buff_strength)).bright_green());
+    } else {
+        println!("Violet laughs, \"We're already married, dear! Now go and slay something.\"");
+    }
+}
@@ -0,0 +1,162 @@
+//! Game calendar: which game day it is, when the next one starts, and the
+//! scheduled daily maintenance for long-running servers.
+//!
+//! A game day begins at the configured reset time in the configured timezone
+//! (see `config::DayConfig`). With a reset time of 04:00, for example, 03:59 on
+//! the 2nd still belongs to game day the 1st.
+use chrono::{DateTime, Days, Local, LocalResult, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use crate::config::{self, DayConfig};
+use crate::db;
+use sqlx::PgPool;
+
+/// The game day that is in progress at `now`.
+pub fn game_date(now: DateTime<Utc>) -> NaiveDate {
+    game_date_in(&config::get().day, now)
+}
+
+/// The instant at which the game day following the one in progress at `now` begins.
+pub fn next_reset(now: DateTime<Utc>) -> DateTime<Utc> {
+    next_reset_in(&config::get().day, now)
+}
+
+/// The game day in progress at `now` under the given day settings.
+fn game_date_in(day: &DayConfig, now: DateTime<Utc>) -> NaiveDate {
+    let wall_clock = match day.timezone {
+        Some(tz) => now.with_timezone(&tz).naive_local(),
+        None => now.with_timezone(&Local).naive_local(),
+    };
+    let date = (wall_clock - day.reset_time.signed_duration_since(chrono::NaiveTime::MIN)).date();
+    // Around a daylight saving change the wall clock alone can disagree with
+    // when the day actually started (or even run backwards), so settle it by
+    // the same boundaries `next_reset` uses
+    if now < day_start(day, date) {
+        date - Days::new(1)
+    } else if now >= day_start(day, date + Days::new(1)) {
+        date + Days::new(1)
+    } else {
+        date
+    }
+}
+
+/// The instant the next game day begins under the given day settings.
+fn next_reset_in(day: &DayConfig, now: DateTime<Utc>) -> DateTime<Utc> {
+    day_start(day, game_date_in(day, now) + Days::new(1))
+}
+
+/// The instant at which the game day `date` begins.
+fn day_start(day: &DayConfig, date: NaiveDate) -> DateTime<Utc> {
+    let boundary = date.and_time(day.reset_time);
+    match day.timezone {
+        Some(tz) => to_utc(&tz, boundary),
+        None => to_utc(&Local, boundary),
+    }
+}
+
+/// Convert a wall-clock time in `tz` to UTC.
+///
+/// Wall-clock times repeated by a daylight saving change resolve to the first
+/// occurrence; times skipped by one resolve to an hour later.
+fn to_utc<Tz: TimeZone>(tz: &Tz, wall_clock: NaiveDateTime) -> DateTime<Utc> {
+    match tz.from_local_datetime(&wall_clock) {
+        LocalResult::Single(t) | LocalResult::Ambiguous(t, _) => t.with_timezone(&Utc),
+        LocalResult::None => to_utc(tz, wall_clock + chrono::Duration::hours(1)),
+    }
+}
+
+/// Run the daily maintenance forever, once at startup and then at every game-day boundary.
+///
+/// This is meant for a long-lived server process; interactive sessions only
+/// check for a pending reset when they start. Missed days are caught up by
+/// `db::daily_reset`, so restarting the task late is harmless.
+///
+/// # Arguments
+///
+/// * `conn` - Database connection pool
+pub async fn run_daily_maintenance(conn: &PgPool) {
+    loop {
+        match db::daily_reset(conn).await {
+            Ok(0) => println!("[{}] Daily maintenance: already up to date.", Utc::now().format("%Y-%m-%d %H:%M:%S")),
+            Ok(days) => println!("[{}] Daily maintenance: advanced {} game day(s).", Utc::now().format("%Y-%m-%d %H:%M:%S"), days),
+            Err(e) => eprintln!("Failed to perform daily reset: {}", e),
+        }
+        let now = Utc::now();
+        let next = next_reset(now);
+        println!("Next daily maintenance at {} UTC.", next.format("%Y-%m-%d %H:%M:%S"));
+        tokio::time::sleep((next - now).to_std().unwrap_or_default()).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveTime;
+
+    /// Day settings for New York with the reset at the given wall-clock time.
+    fn new_york(hour: u32, minute: u32) -> DayConfig {
+        DayConfig {
+            reset_time: NaiveTime::from_hms_opt(hour, minute, 0).unwrap(),
+            timezone: Some(chrono_tz::America::New_York),
+        }
+    }
+
+    fn utc(y: i32, m: u32, d: u32, h: u32, min: u32, s: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(y, m, d, h, min, s).unwrap()
+    }
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn the_day_turns_over_at_the_reset_time() {
+        let day = new_york(4, 0);
+        // 03:59:59 and 04:00 EST
+        assert_eq!(game_date_in(&day, utc(2026, 1, 15, 8, 59, 59)), date(2026, 1, 14));
+        assert_eq!(game_date_in(&day, utc(2026, 1, 15, 9, 0, 0)), date(2026, 1, 15));
+        assert_eq!(next_reset_in(&day, utc(2026, 1, 15, 8, 59, 59)), utc(2026, 1, 15, 9, 0, 0));
+        assert_eq!(next_reset_in(&day, utc(2026, 1, 15, 9, 0, 0)), utc(2026, 1, 16, 9, 0, 0));
+    }
+
+    #[test]
+    fn a_reset_skipped_by_spring_forward_happens_an_hour_later() {
+        // On 8 March 2026 New York clocks jump from 02:00 EST to 03:00 EDT, so 02:30 never happens
+        let day = new_york(2, 30);
+        assert_eq!(next_reset_in(&day, utc(2026, 3, 7, 12, 0, 0)), utc(2026, 3, 8, 7, 30, 0));
+        // 03:10 EDT is past 02:30 on the wall clock, but the day has not turned over yet
+        assert_eq!(game_date_in(&day, utc(2026, 3, 8, 7, 10, 0)), date(2026, 3, 7));
+        assert_eq!(game_date_in(&day, utc(2026, 3, 8, 7, 29, 59)), date(2026, 3, 7));
+        assert_eq!(game_date_in(&day, utc(2026, 3, 8, 7, 30, 0)), date(2026, 3, 8));
+        // The day after, 02:30 EDT exists again
+        assert_eq!(next_reset_in(&day, utc(2026, 3, 8, 7, 30, 0)), utc(2026, 3, 9, 6, 30, 0));
+    }
+
+    #[test]
+    fn a_reset_repeated_by_fall_back_happens_once() {
+        // On 1 November 2026 New York clocks fall back from 02:00 EDT to 01:00 EST, so 01:30 happens twice
+        let day = new_york(1, 30);
+        assert_eq!(next_reset_in(&day, utc(2026, 10, 31, 12, 0, 0)), utc(2026, 11, 1, 5, 30, 0));
+        assert_eq!(game_date_in(&day, utc(2026, 11, 1, 5, 29, 59)), date(2026, 10, 31));
+        assert_eq!(game_date_in(&day, utc(2026, 11, 1, 5, 30, 0)), date(2026, 11, 1));
+        // 01:00 EST, back before 01:30 on the wall clock: the day must not go backwards
+        assert_eq!(game_date_in(&day, utc(2026, 11, 1, 6, 0, 0)), date(2026, 11, 1));
+        // 01:30 EST, the second time around, is not a new day either
+        assert_eq!(game_date_in(&day, utc(2026, 11, 1, 6, 30, 0)), date(2026, 11, 1));
+        assert_eq!(next_reset_in(&day, utc(2026, 11, 1, 6, 0, 0)), utc(2026, 11, 2, 6, 30, 0));
+    }
+
+    #[test]
+    fn every_day_of_the_year_starts_where_the_last_one_ends() {
+        for (hour, minute) in [(0, 0), (1, 30), (2, 30), (4, 0)] {
+            let day = new_york(hour, minute);
+            let mut start = day_start(&day, date(2026, 1, 1));
+            for _ in 0..365 {
+                let today = game_date_in(&day, start);
+                assert_eq!(game_date_in(&day, start - chrono::Duration::seconds(1)), today - Days::new(1));
+                let next = next_reset_in(&day, start);
+                assert!(next > start);
+                assert_eq!(game_date_in(&day, next), today + Days::new(1));
+                start = next;
+            }
+        }
+    }
+}
@@ -0,0 +1,94 @@
+//! Live chat between nodes.
+//!
+//! Messages are stored in the `chat_messages` table and announced with a
+//! Postgres `NOTIFY` on the `lord_chat` channel. Every session's `Listener`
+//! fetches the messages meant for its player from the chat `Feed` and queues
+//! them so they appear before the next prompt. Since delivery goes through the
+//! database it works the same for sessions in one server process and for
+//! separate door processes.
+use colored::Colorize;
+use sqlx::PgPool;
+use crate::db;
+use crate::game::Player;
+use crate::game::listener::Feed;
+use crate::ui;
+
+/// Postgres notification channel announcing new chat messages.
+pub const CHAT_CHANNEL: &str = "lord_chat";
+
+/// Longest chat message accepted, in characters.
+const MAX_MESSAGE_LEN: usize = 200;
+
+/// The feed of messages addressed to the player or broadcast to everyone.
+///
+/// Only messages sent from now on are delivered.
+pub async fn feed(conn: &PgPool, player_id: i32) -> Result<Feed, sqlx::Error> {
+    let last_seen = db::latest_chat_message_id(conn).await?;
+    Ok(Feed::new(CHAT_CHANNEL, "chat messages", last_seen, move |conn, after| async move {
+        let messages = db::get_chat_messages(&conn, player_id, after).await?;
+        Ok(messages
+            .into_iter()
+            .map(|message| {
+                let line = if message.broadcast {
+                    format!("[Chat] {}: {}", message.from_name, message.body)
+                } else {
+                    format!("[Private] {}: {}", message.from_name, message.body)
+                };
+                (message.id, Some(line.bright_cyan().to_string()))
+            })
+            .collect())
+    }))
+}
+
+/// Ask the player for a recipient and a message, then send it.
+///
+/// The recipient is chosen by node number from the "Who's Online" list;
+/// leaving it blank sends the message to everyone online.
+pub async fn send_message(conn: &PgPool, player: &Player) {
+    let online = match db::list_online_players(conn).await {
+        Ok(list) => list,
+        Err(e) => {
+            println!("Could not retrieve the list of players online: {}", e);
+            return;
+        }
+    };
+    println!("\nHeroes in the realm:");
+    for other in &online {
+        println!("  Node {}: {} ({})", other.node, other.name, other.location);
+    }
+    let input = ui::prompt("Send to which node? (leave blank for everyone): ");
+    let recipient = if input.trim().is_empty() {
+        None
+    } else {
+        let Ok(node) = input.trim().parse::<i32>() else {
+            println!("Invalid node number.");
+            return;
+        };
+        match online.iter().find(|other| other.node == node) {
+            Some(other) if other.player_id == player.id => {
+                println!("You mutter to yourself. Nobody else hears it.");
+                return;
+            }
+            Some(other) => Some(other),
+            None => {
+                println!("Nobody is on node {}.", node);
+                return;
+            }
+        }
+    };
+
+    let body = ui::prompt("Message: ");
+    let body = body.trim();
+    if body.is_empty() {
+        println!("You decide to keep quiet.");
+        return;
+    }
+    let body: String = body.chars().take(MAX_MESSAGE_LEN).collect();
+    match db::send_chat_message(conn, player, recipient.map(|other| other.player_id), &body).await {
+        Ok(()) => match recipient {
+            Some(other) => println!("Your message is on its way to {}.", other.name),
+            None => println!("You shout your message across the realm."),
+        },
+        Err(e) => println!("Your message could not be sent: {}", e),
+    }
+}
@@ -0,0 +1,309 @@
+//! Structured game events.
+//!
+//! Everything notable that happens in the realm (monster kills, deaths, duels,
+//! level-ups, marriages, dragon slayings, purchases, bounties, bets) is recorded as a `GameEvent` in the
+//! `events` table: who did it, to whom, the gold and experience that changed
+//! hands, and a JSON payload with the details. The news is rendered from these
+//! events, and the table keeps the full record for statistics, settling
+//! disputes over duels and balancing queries long after the news is pruned.
+//! Each event also updates the lifetime statistics of the players involved.
+use crate::config::rules;
+use crate::db::{NewsKind, StatsDelta};
+use crate::game::Player;
+use serde_json::{Value, json};
+
+/// What kind of thing happened.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventKind {
+    /// A hero defeated a monster in the forest
+    MonsterKill,
+    /// A hero was killed by a monster or the Red Dragon
+    Death,
+    /// Two heroes fought a duel
+    Duel,
+    /// A hero gained a level
+    LevelUp,
+    /// A hero got married
+    Marriage,
+    /// A hero slew the Red Dragon
+    DragonKill,
+    /// A hero bought something
+    Purchase,
+    /// A hero put a price on another hero's head
+    BountyPosted,
+    /// A hero collected the bounty on the hero they defeated
+    BountyClaimed,
+    /// A hero won or lost a bet at the tavern
+    Gamble,
+}
+
+impl EventKind {
+    /// The value stored in the `kind` column.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            EventKind::MonsterKill => "monster_kill",
+            EventKind::Death => "death",
+            EventKind::Duel => "duel",
+            EventKind::LevelUp => "level_up",
+            EventKind::Marriage => "marriage",
+            EventKind::DragonKill => "dragon_kill",
+            EventKind::Purchase => "purchase",
+            EventKind::BountyPosted => "bounty_posted",
+            EventKind::BountyClaimed => "bounty_claimed",
+            EventKind::Gamble => "gamble",
+        }
+    }
+
+    /// The news category the event is reported under.
+    pub fn news_kind(self) -> NewsKind {
+        match self {
+            EventKind::MonsterKill => NewsKind::Forest,
+            EventKind::Death => NewsKind::Death,
+            EventKind::Duel => NewsKind::Duel,
+            EventKind::LevelUp => NewsKind::LevelUp,
+            EventKind::Marriage => NewsKind::Marriage,
+            EventKind::DragonKill => NewsKind::Dragon,
+            EventKind::Purchase => NewsKind::System,
+            EventKind::BountyPosted | EventKind::BountyClaimed => NewsKind::Bounty,
+            EventKind::Gamble => NewsKind::Tavern,
+        }
+    }
+}
+
+/// A single game event, as recorded in the `events` table.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GameEvent {
+    /// What happened
+    pub kind: EventKind,
+    /// The player who did it (the winner, for duels)
+    pub actor_id: Option<i32>,
+    /// The other player involved (the loser, for duels)
+    pub target_id: Option<i32>,
+    /// Gold gained by the actor (negative for gold spent)
+    pub gold: i32,
+    /// Experience gained by the actor
+    pub exp: i32,
+    /// Details of the event, including the names involved at the time
+    pub payload: Value,
+}
+
+impl GameEvent {
+    /// A monster slain in the forest, with the rewards it gave.
+    pub fn monster_kill(player: &Player, monster: &str, monster_attack: i32, gold: i32, exp: i32) -> Self {
+        GameEvent {
+            kind: EventKind::MonsterKill,
+            actor_id: Some(player.id),
+            target_id: None,
+            gold,
+            exp,
+            payload: json!({ "player": player.name, "monster": monster, "monster_attack": monster_attack }),
+        }
+    }
+
+    /// A hero killed by `killer`, a monster or the Red Dragon.
+    pub fn death(player: &Player, killer: &str) -> Self {
+        GameEvent {
+            kind: EventKind::Death,
+            actor_id: Some(player.id),
+            target_id: None,
+            gold: 0,
+            exp: 0,
+            payload: json!({ "player": player.name, "killer": killer }),
+        }
+    }
+
+    /// A duel won by `winner`, who took `gold`, `exp` and `rating_change` rating points from it.
+    ///
+    /// `challenger_id` records who started the fight.
+    pub fn duel(winner: &Player, loser: &Player, challenger_id: i32, gold: i32, exp: i32, rating_change: i32) -> Self {
+        GameEvent {
+            kind: EventKind::Duel,
+            actor_id: Some(winner.id),
+            target_id: Some(loser.id),
+            gold,
+            exp,
+            payload: json!({
+                "winner": winner.name,
+                "loser": loser.name,
+                "challenger_id": challenger_id,
+                "winner_level": winner.level,
+                "loser_level": loser.level,
+                "rating_change": rating_change,
+            }),
+        }
+    }
+
+    /// A hero reaching a new level.
+    pub fn level_up(player: &Player) -> Self {
+        GameEvent {
+            kind: EventKind::LevelUp,
+            actor_id: Some(player.id),
+            target_id: None,
+            gold: 0,
+            exp: 0,
+            payload: json!({ "player": player.name, "level": player.level }),
+        }
+    }
+
+    /// A hero marrying `spouse`.
+    pub fn marriage(player: &Player, spouse: &str) -> Self {
+        GameEvent {
+            kind: EventKind::Marriage,
+            actor_id: Some(player.id),
+            target_id: None,
+            gold: 0,
+            exp: 0,
+            payload: json!({ "player": player.name, "spouse": spouse }),
+        }
+    }
+
+    /// A hero slaying the Red Dragon, with their dragon kill count including this one.
+    pub fn dragon_kill(player: &Player) -> Self {
+        GameEvent {
+            kind: EventKind::DragonKill,
+            actor_id: Some(player.id),
+            target_id: None,
+            gold: 0,
+            exp: 0,
+            payload: json!({ "player": player.name, "dragon_kills": player.dragon_kills }),
+        }
+    }
+
+    /// A purchase of `item` for `price` gold.
+    pub fn purchase(player: &Player, item: &str, price: i32) -> Self {
+        GameEvent {
+            kind: EventKind::Purchase,
+            actor_id: Some(player.id),
+            target_id: None,
+            gold: -price,
+            exp: 0,
+            payload: json!({ "player": player.name, "item": item }),
+        }
+    }
+
+    /// A bounty of `amount` gold posted by `poster` on `target`'s head.
+    pub fn bounty_posted(poster: &Player, target: &Player, amount: i32) -> Self {
+        GameEvent {
+            kind: EventKind::BountyPosted,
+            actor_id: Some(poster.id),
+            target_id: Some(target.id),
+            gold: -amount,
+            exp: 0,
+            payload: json!({ "poster": poster.name, "target": target.name }),
+        }
+    }
+
+    /// The `amount` gold in bounties on `loser`'s head collected by `winner`.
+    pub fn bounty_claimed(winner: &Player, loser: &Player, amount: i32) -> Self {
+        GameEvent {
+            kind: EventKind::BountyClaimed,
+            actor_id: Some(winner.id),
+            target_id: Some(loser.id),
+            gold: amount,
+            exp: 0,
+            payload: json!({ "winner": winner.name, "target": loser.name }),
+        }
+    }
+
+    /// A bet on `game` that won (or, if negative, lost) `net` gold.
+    ///
+    /// Wins of at least `big_win_gold` are flagged for the news.
+    pub fn gamble(player: &Player, game: &str, net: i32) -> Self {
+        GameEvent {
+            kind: EventKind::Gamble,
+            actor_id: Some(player.id),
+            target_id: None,
+            gold: net,
+            exp: 0,
+            payload: json!({ "player": player.name, "game": game, "big_win": net >= rules().big_win_gold }),
+        }
+    }
+
+    /// The changes this event makes to the lifetime statistics of each player involved.
+    pub fn stats_changes(&self) -> Vec<(i32, StatsDelta)> {
+        let mut changes = Vec::new();
+        let Some(actor) = self.actor_id else {
+            return changes;
+        };
+        let earned = i64::from(self.gold.max(0));
+        match self.kind {
+            EventKind::MonsterKill => changes.push((actor, StatsDelta {
+                monster: self.payload["monster"].as_str().map(str::to_string),
+                gold_earned: earned,
+                ..StatsDelta::default()
+            })),
+            EventKind::Death => changes.push((actor, StatsDelta { deaths: 1, ..StatsDelta::default() })),
+            EventKind::Duel => {
+                changes.push((actor, StatsDelta { duels_won: 1, gold_earned: earned, ..StatsDelta::default() }));
+                if let Some(loser) = self.target_id {
+                    changes.push((loser, StatsDelta { duels_lost: 1, deaths: 1, ..StatsDelta::default() }));
+                }
+            }
+            EventKind::LevelUp => {
+                let level = self.payload["level"].as_i64().unwrap_or(1) as i32;
+                changes.push((actor, StatsDelta { highest_level: level, ..StatsDelta::default() }));
+            }
+            EventKind::DragonKill => changes.push((actor, StatsDelta { dragon_kills: 1, ..StatsDelta::default() })),
+            EventKind::BountyClaimed => {
+                changes.push((actor, StatsDelta { gold_earned: earned, ..StatsDelta::default() }));
+            }
+            EventKind::Gamble => {
+                let spent = i64::from((-self.gold).max(0));
+                changes.push((actor, StatsDelta { gold_earned: earned, gold_spent: spent, ..StatsDelta::default() }));
+            }
+            EventKind::Purchase | EventKind::BountyPosted => {
+                let spent = i64::from((-self.gold).max(0));
+                changes.push((actor, StatsDelta { gold_spent: spent, ..StatsDelta::default() }));
+            }
+            EventKind::Marriage => {}
+        }
+        changes
+    }
+
+    /// The news line reporting this event, or `None` if it is not newsworthy.
+    pub fn news_text(&self) -> Option<String> {
+        let text = |key: &str| self.payload[key].as_str().unwrap_or("Someone").to_string();
+        let player = text("player");
+        match self.kind {
+            EventKind::MonsterKill => {
+                // Only particularly strong monsters make the news, to avoid spam
+                let attack = self.payload["monster_attack"].as_i64().unwrap_or(0);
+                (attack > 10).then(|| format!("{} defeated a {} in the forest.", player, text("monster")))
+            }
+            EventKind::Death if text("killer") == "Red Dragon" => {
+                Some(format!("{} was burned to a crisp by the Red Dragon.", player))
+            }
+            EventKind::Death => Some(format!("{} was slain by a {} in the forest.", player, text("killer"))),
+            EventKind::Duel => {
+                let challenger_won = self.payload["challenger_id"].as_i64() == self.actor_id.map(i64::from);
+                Some(if challenger_won {
+                    format!("{} defeated {} in a duel!", text("winner"), text("loser"))
+                } else {
+                    format!("{} was killed by {} in a duel!", text("loser"), text("winner"))
+                })
+            }
+            EventKind::LevelUp => Some(format!("{} has reached Level {}!", player, self.payload["level"])),
+            EventKind::Marriage if text("spouse") == "Violet" => {
+                Some(format!("{} has married Violet, the tavern barmaid!", player))
+            }
+            EventKind::Marriage => Some(format!("{} has married {}!", player, text("spouse"))),
+            EventKind::DragonKill => Some(format!("{} has slain the Red Dragon! The town rejoices.", player)),
+            EventKind::Purchase => None,
+            EventKind::BountyPosted => Some(format!(
+                "{} has placed a bounty of {} gold on {}'s head!",
+                text("poster"),
+                -self.gold,
+                text("target")
+            )),
+            EventKind::BountyClaimed => Some(format!(
+                "{} has claimed the {} gold bounty on {}'s head!",
+                text("winner"),
+                self.gold,
+                text("target")
+            )),
+            EventKind::Gamble => self.payload["big_win"].as_bool().unwrap_or(false).then(|| {
+                format!("{} won {} gold at {} in the Dark Cloak Tavern!", player, self.gold, text("game"))
+            }),
+        }
+    }
+}
@@ -1,107 +1,320 @@
 //! Forest exploration: encountering and fighting monsters.
 use rand::Rng;
 use colored::Colorize;
-use crate::ui::ansi_art; // Import ANSI art
-use crate::game::{Player, try_level_up};
+use crate::ui::{ansi_art, session}; // Import ANSI art
+use crate::config::rules;
+use crate::game::buffs::{self, Buff};
+use crate::game::monsters::{self, Ability, MonsterDef};
+use crate::game::rng::GameRng;
+use crate::game::status::Conditions;
+use crate::game::{GameEvent, Player, gain_level, gems, inventory, online, try_level_up, save_player, season};
 use crate::db;
 use sqlx::PgPool;
 
-/// A simple monster representation for forest fights.
+/// Hit points of the Red Dragon.
+const RED_DRAGON_HP: i32 = 180;
+/// Attack power of the Red Dragon.
+const RED_DRAGON_ATTACK: i32 = 18;
+
+/// A monster met in the forest, scaled to the hero's level.
 struct Monster {
-    name: &'static str,
+    /// The catalog entry the monster was made from
+    def: &'static MonsterDef,
     hp: i32,
+    max_hp: i32,
     attack: i32,
     exp_reward: i32,
     gold_reward: i32,
 }
 
 /// Generate a random monster appropriate for the player's level.
-fn generate_monster(player_level: i32) -> Monster {
-    // Some monster templates (name, base HP, base attack)
-    static MONSTER_TEMPLATES: &[(&str, i32, i32)] = &[
-        ("Wild Boar", 15, 4),
-        ("Goblin", 20, 5),
-        ("Ogre", 30, 6),
-        ("Giant Spider", 25, 5),
-        ("Black Knight", 35, 8),
-        ("Forest Dragon", 50, 12),
-    ];
-    let mut rng = rand::rng();
+fn generate_monster(player_level: i32, rng: &mut GameRng) -> Monster {
+    let templates = monsters::catalog();
     // Pick a random template
-    let (name, base_hp, base_attack) = MONSTER_TEMPLATES[rng.random_range(0..MONSTER_TEMPLATES.len())];
+    let def = &templates[rng.random_range(0..templates.len())];
     // Scale monster stats with player level
     let level_factor = 1 + (player_level - 1) / 2;  // moderate scaling
-    let hp = base_hp * level_factor + rng.random_range(0..=5 * player_level);
-    let attack = base_attack * level_factor + rng.random_range(0..=player_level);
+    let hp = def.hp * level_factor + rng.random_range(0..=5 * player_level);
+    let attack = def.attack * level_factor + rng.random_range(0..=player_level);
     // Determine rewards based on monster strength
     let exp_reward = hp / 2 + attack;
     let gold_reward = rng.random_range(1..=attack * 3);
     Monster {
-        name,
+        def,
         hp,
+        max_hp: hp,
         attack,
         exp_reward: exp_reward.max(1),
         gold_reward: gold_reward.max(1),
     }
 }
 
+/// How a monster's turn went.
+struct MonsterTurn {
+    /// Whether the monster fled instead of striking
+    fled: bool,
+    /// What happened, line by line, for the caller to show
+    narration: Vec<String>,
+}
+
+/// The damage of one blow from a hero with the given attack.
+fn strike(attack: i32, rng: &mut GameRng) -> i32 {
+    rng.random_range(1..=attack)
+}
+
+/// The monster's turn: its special abilities come into play, then it strikes
+/// once or twice, possibly inflicting its status effect.
+fn monster_turn(monster: &mut Monster, player: &mut Player, conditions: &mut Conditions, rng: &mut GameRng) -> MonsterTurn {
+    let name = monster.def.name.as_str();
+    let mut narration = Vec::new();
+    let mut strikes = 1;
+    let mut fled = false;
+    for ability in &monster.def.abilities {
+        match *ability {
+            Ability::Flee { chance, below } if monster.hp * 100 < monster.max_hp * below => {
+                fled = rng.random_range(0..100) < chance;
+            }
+            Ability::Regenerate { percent } if monster.hp < monster.max_hp => {
+                let healed = (monster.max_hp * percent / 100).max(1).min(monster.max_hp - monster.hp);
+                monster.hp += healed;
+                narration.push(format!("The {}'s wounds knit together before your eyes. (+{} HP)", name, healed));
+            }
+            Ability::DoubleStrike { chance } if rng.random_range(0..100) < chance => strikes = 2,
+            _ => {}
+        }
+    }
+    if fled {
+        narration.push(format!("The {} turns tail and flees into the trees!", name).yellow().to_string());
+        return MonsterTurn { fled, narration };
+    }
+    if strikes == 2 {
+        narration.push(format!("The {} attacks in a furious flurry!", name));
+    }
+    for _ in 0..strikes {
+        let damage_to_player = rng.random_range(1..=monster.attack);
+        player.current_hp -= damage_to_player;
+        narration.push(format!("The {} {} you with its {} for {} damage.", name, monster.def.verb, monster.def.weapon, damage_to_player));
+        if let Some(percent) = monster.def.life_drain() {
+            let drained = (damage_to_player * percent / 100).max(1).min(monster.max_hp - monster.hp);
+            if drained > 0 {
+                monster.hp += drained;
+                narration.push(format!("The {} drinks in your life force. (+{} HP)", name, drained));
+            }
+        }
+        if player.current_hp <= 0 {
+            break;
+        }
+        if let Some(affliction) = monster.def.status {
+            if rng.random_range(0..100) < affliction.chance {
+                narration.push(conditions.afflict(player, affliction.effect, monster.attack / 3).bright_magenta().to_string());
+            }
+        }
+    }
+    MonsterTurn { fled, narration }
+}
+
+/// Something the hero can fight: a forest monster or the Red Dragon.
+trait Foe {
+    /// Remaining hit points
+    fn hp_mut(&mut self) -> &mut i32;
+    /// Describe a blow the hero lands on the foe.
+    fn struck(&self, damage: i32) -> String;
+    /// The foe's turn against the hero.
+    fn turn(&mut self, player: &mut Player, conditions: &mut Conditions, rng: &mut GameRng) -> MonsterTurn;
+}
+
+impl Foe for Monster {
+    fn hp_mut(&mut self) -> &mut i32 {
+        &mut self.hp
+    }
+
+    fn struck(&self, damage: i32) -> String {
+        format!("You hit the {} for {} damage.", self.def.name, damage)
+    }
+
+    fn turn(&mut self, player: &mut Player, conditions: &mut Conditions, rng: &mut GameRng) -> MonsterTurn {
+        monster_turn(self, player, conditions, rng)
+    }
+}
+
+/// The Red Dragon in its lair.
+struct RedDragon {
+    hp: i32,
+}
+
+impl Foe for RedDragon {
+    fn hp_mut(&mut self) -> &mut i32 {
+        &mut self.hp
+    }
+
+    fn struck(&self, damage: i32) -> String {
+        format!("You strike the Red Dragon for {} damage.", damage)
+    }
+
+    fn turn(&mut self, player: &mut Player, _conditions: &mut Conditions, rng: &mut GameRng) -> MonsterTurn {
+        let damage_to_player = strike(RED_DRAGON_ATTACK, rng);
+        player.current_hp -= damage_to_player;
+        MonsterTurn {
+            fled: false,
+            narration: vec![format!("The Red Dragon breathes fire on you for {} damage.", damage_to_player)],
+        }
+    }
+}
+
+/// How a round of battle ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Round {
+    /// Both are still standing
+    Fighting,
+    /// The foe was slain
+    Won,
+    /// The foe ran away
+    Fled,
+    /// The hero was killed
+    Lost,
+}
+
+/// Fight one round of battle against `foe`.
+///
+/// Poison and bleeding strike first. Then the hero strikes with `attack`,
+/// unless they are stunned or `turn_used` (by an item, say), and the foe takes
+/// its turn if it still stands. What happened is added to `narration` for the
+/// caller to show. A hero killed in the round is left dead at 0 HP; rewards,
+/// news and saving are up to the caller.
+///
+/// Interactive fights and the balance simulator both fight through this, so
+/// the simulator's heroes meet exactly the odds the players do.
+fn fight_round(
+    foe: &mut impl Foe,
+    player: &mut Player,
+    attack: i32,
+    turn_used: bool,
+    conditions: &mut Conditions,
+    rng: &mut GameRng,
+    narration: &mut Vec<String>,
+) -> Round {
+    let suffered = conditions.tick(player);
+    if suffered > 0 {
+        narration.push(format!("Your afflictions cost you {} HP.", suffered).magenta().to_string());
+    }
+    if player.current_hp > 0 {
+        if conditions.stunned() {
+            narration.push("You are stunned and cannot act!".to_string());
+        } else if !turn_used {
+            let damage_to_foe = strike(attack, rng);
+            *foe.hp_mut() -= damage_to_foe;
+            narration.push(foe.struck(damage_to_foe));
+            if *foe.hp_mut() <= 0 {
+                *foe.hp_mut() = 0;
+                return Round::Won;
+            }
+        }
+        let turn = foe.turn(player, conditions, rng);
+        narration.extend(turn.narration);
+        if turn.fled {
+            return Round::Fled;
+        }
+        if player.current_hp > 0 {
+            return Round::Fighting;
+        }
+    }
+    player.current_hp = 0;
+    player.alive = false;
+    Round::Lost
+}
+
+/// Fight rounds against `foe` until the battle is decided, asking the player
+/// each round whether to attack or use an item if they carry any.
+async fn battle(conn: &PgPool, foe: &mut impl Foe, player: &mut Player, rng: &mut GameRng) -> Round {
+    let attack = player.attack + buffs::amount(conn, player.id, Buff::Strength).await;
+    let mut has_items = inventory::has_combat_items(conn, player).await;
+    let mut conditions = Conditions::default();
+    loop {
+        // Using an item takes the player's turn; a stunned player has no turn to use
+        let mut turn_used = false;
+        if has_items && !conditions.is_stunned() {
+            let action = crate::ui::prompt("(A)ttack or (U)se an item? [A]: ");
+            if action.trim().eq_ignore_ascii_case("U") {
+                turn_used = inventory::use_in_combat(conn, player).await;
+                has_items = inventory::has_combat_items(conn, player).await;
+            }
+        }
+        let mut narration = Vec::new();
+        let round = fight_round(foe, player, attack, turn_used, &mut conditions, rng, &mut narration);
+        for line in &narration {
+            println!("{}", line);
+        }
+        if round != Round::Fighting {
+            return round;
+        }
+    }
+}
+
+/// Fight rounds against `foe` until the battle is decided, with no output and
+/// no database. The hero attacks every round, with no buffs or items.
+fn battle_unattended(foe: &mut impl Foe, player: &mut Player, rng: &mut GameRng) -> Round {
+    let attack = player.attack;
+    let mut conditions = Conditions::default();
+    loop {
+        let round = fight_round(foe, player, attack, false, &mut conditions, rng, &mut Vec::new());
+        if round != Round::Fighting {
+            return round;
+        }
+    }
+}
+
 /// Enter the forest and fight monsters until the player chooses to leave or runs out of fights/HP.
-pub async fn explore_forest(conn: &PgPool, player: &mut Player) {
+pub async fn explore_forest(conn: &PgPool, player: &mut Player, rng: &mut GameRng) {
     if player.forest_fights <= 0 {
         println!("You've exhausted your forest fights for today.");
         return;
     }
+    online::set_location(conn, player, "Forest").await;
     println!("{}", ansi_art::FOREST_SCENE); // Show ANSI art before exploration starts
+    if player.level >= rules().red_dragon_level {
+        let choice = crate::ui::prompt("You sense the Red Dragon's lair nearby. Seek out the Red Dragon? (Y/N): ");
+        if choice.trim().eq_ignore_ascii_case("Y") {
+            fight_red_dragon(conn, player, rng).await;
+            return;
+        }
+    }
     println!("You venture into the forest... ({} fights left today)", player.forest_fights);
-    let mut rng = rand::rng();
 
     // Loop for multiple fights
-    while player.forest_fights > 0 && player.alive {
+    while player.forest_fights > 0 && player.alive && !session::expired() {
         // Encounter a monster
-        let mut monster = generate_monster(player.level);
-        println!("\nA wild {} appears! [HP: {}, Attack: {}]", monster.name, monster.hp, monster.attack);
-        println!("{}", format!("Fight! {} vs {}", player.name, monster.name).yellow().bold());
-        // Battle loop
-        while player.alive && monster.hp > 0 {
-            // Player attacks first each round
-            let damage_to_monster = rng.random_range(1..=player.attack);
-            monster.hp -= damage_to_monster;
-            println!("You hit the {} for {} damage.", monster.name, damage_to_monster);
-            if monster.hp <= 0 {
-                // Monster defeated
-                monster.hp = 0;
-                println!("{}", format!("You have slain the {}!", monster.name).bright_green());
+        let mut monster = generate_monster(player.level, rng);
+        println!("\nA wild {} appears! [HP: {}, Attack: {}]", monster.def.name, monster.hp, monster.attack);
+        println!("{}", format!("Fight! {} vs {}", player.name, monster.def.name).yellow().bold());
+        match battle(conn, &mut monster, player, rng).await {
+            Round::Won => {
+                let name = monster.def.name.as_str();
+                println!("{}", monster.def.death.bright_green());
                 // Rewards
                 player.exp += monster.exp_reward;
                 player.gold += monster.gold_reward;
                 println!("You gain {} XP and {} gold.", monster.exp_reward, monster.gold_reward);
                 // Check for level-up(s)
                 try_level_up(player, conn).await;
-                // Log monster kill event (optional: log only special monsters to avoid spam)
-                // We'll log if monster was particularly strong (e.g., Forest Dragon or high level)
-                if monster.attack > 10 {
-                    let news = format!("{} defeated a {} in the forest.", player.name, monster.name);
-                    if let Err(e) = db::log_event(conn, &news).await {
-                        eprintln!("Failed to log monster defeat: {}", e);
-                    }
+                // Log monster kill event (only particularly strong monsters make the news)
+                let event = GameEvent::monster_kill(player, name, monster.attack, monster.gold_reward, monster.exp_reward);
+                if let Err(e) = db::log_event(conn, &event).await {
+                    eprintln!("Failed to log monster defeat: {}", e);
                 }
-                break;
+                gems::find_gems(player, name, rng);
+                let drops = inventory::roll_drops(player, rng);
+                inventory::keep_drops(conn, player, name, drops).await;
             }
-            // Monster strikes back if still alive
-            let damage_to_player = rng.random_range(1..=monster.attack);
-            player.current_hp -= damage_to_player;
-            println!("The {} hits you for {} damage.", monster.name, damage_to_player);
-            if player.current_hp <= 0 {
-                // Player dies
-                player.current_hp = 0;
-                player.alive = false;
+            Round::Lost => {
                 println!("{}", "You have been killed in battle...".bright_red().bold());
-                let news = format!("{} was slain by a {} in the forest.", player.name, monster.name);
-                db::log_event(conn, &news).await.ok();
+                db::log_event(conn, &GameEvent::death(player, &monster.def.name)).await.ok();
             }
-        } // end battle loop
+            Round::Fled | Round::Fighting => {}
+        }
 
         player.forest_fights -= 1;
+        // Persist the outcome of every fight as it happens
+        save_player(conn, player).await;
         if !player.alive {
             // End exploration if player died
             break;
@@ -118,3 +331,207 @@ pub async fn explore_forest(conn: &PgPool, player: &mut Player) {
         }
     }
 }
+
+/// Face the Red Dragon in its lair. Costs one forest fight.
+///
+/// Slaying the dragon is the greatest deed in the realm: it is announced in
+/// the news, counted towards the hero's dragon kills, and the hero is reborn
+/// at Level 1 to begin the journey again.
+async fn fight_red_dragon(conn: &PgPool, player: &mut Player, rng: &mut GameRng) {
+    println!("{}", ansi_art::RED_DRAGON);
+    println!("{}", "The Red Dragon rises from its lair, smoke curling from its nostrils!".bright_red().bold());
+    player.forest_fights -= 1;
+    if battle(conn, &mut RedDragon { hp: RED_DRAGON_HP }, player, rng).await == Round::Lost {
+        println!("{}", "You have been incinerated by the Red Dragon...".bright_red().bold());
+        db::log_event(conn, &GameEvent::death(player, "Red Dragon")).await.ok();
+        save_player(conn, player).await;
+        return;
+    }
+
+    player.dragon_kills += 1;
+    println!("{}", "You have slain the Red Dragon! The realm is saved!".bright_green().bold());
+    if let Err(e) = db::log_event(conn, &GameEvent::dragon_kill(player)).await {
+        eprintln!("Failed to log dragon kill: {}", e);
+    }
+    player.reset_to_starting_stats();
+    println!("You awaken in the town square, a legend reborn at Level 1. (Dragon kills: {})", player.dragon_kills);
+    save_player(conn, player).await;
+    season::check_dragon_rollover(conn, player).await;
+}
+
+/// How a fight fought without anyone at the keyboard ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Unattended {
+    /// The foe was slain and the rewards collected
+    Won,
+    /// The monster ran before it could be slain
+    Fled,
+    /// The hero was killed
+    Died,
+}
+
+impl From<Round> for Unattended {
+    fn from(round: Round) -> Unattended {
+        match round {
+            Round::Won => Unattended::Won,
+            // A battle only ends once the round is decided
+            Round::Fled | Round::Fighting => Unattended::Fled,
+            Round::Lost => Unattended::Died,
+        }
+    }
+}
+
+/// Fight one forest monster to the end with no output and no database, as the
+/// balance simulator does.
+///
+/// The battle is fought round by round as in a real fight, with the hero
+/// attacking every round. Rewards, level-ups and gems are collected as in a
+/// real fight too; item drops are rolled, but a simulated hero has no pack to
+/// keep them in.
+pub fn fight_monster_unattended(player: &mut Player, rng: &mut GameRng) -> Unattended {
+    let mut monster = generate_monster(player.level, rng);
+    player.forest_fights -= 1;
+    let outcome = Unattended::from(battle_unattended(&mut monster, player, rng));
+    if outcome == Unattended::Won {
+        player.exp += monster.exp_reward;
+        player.gold += monster.gold_reward;
+        while gain_level(player) {}
+        gems::roll_gems(player, rng);
+        inventory::roll_drops(player, rng);
+    }
+    outcome
+}
+
+/// Fight the Red Dragon to the end with no output and no database.
+///
+/// A hero who slays the dragon is reborn at Level 1, as in a real fight.
+pub fn fight_dragon_unattended(player: &mut Player, rng: &mut GameRng) -> Unattended {
+    player.forest_fights -= 1;
+    let outcome = Unattended::from(battle_unattended(&mut RedDragon { hp: RED_DRAGON_HP }, player, rng));
+    if outcome == Unattended::Won {
+        player.dragon_kills += 1;
+        player.reset_to_starting_stats();
+    }
+    outcome
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::rng::seeded;
+
+    fn hero() -> Player {
+        Player {
+            name: "Tester".to_string(),
+            level: 5,
+            current_hp: 500,
+            max_hp: 500,
+            attack: 10,
+            defense: 5,
+            alive: true,
+            ..Player::default()
+        }
+    }
+
+    fn monster(name: &str, hp: i32, max_hp: i32) -> Monster {
+        let def = monsters::catalog().iter().find(|m| m.name == name).expect("monster in catalog");
+        Monster { def, hp, max_hp, attack: def.attack, exp_reward: 1, gold_reward: 1 }
+    }
+
+    #[test]
+    fn same_seed_meets_same_monster() {
+        for seed in 0..50 {
+            let a = generate_monster(5, &mut seeded(seed));
+            let b = generate_monster(5, &mut seeded(seed));
+            assert_eq!(
+                (&a.def.name, a.hp, a.attack, a.exp_reward, a.gold_reward),
+                (&b.def.name, b.hp, b.attack, b.exp_reward, b.gold_reward)
+            );
+        }
+    }
+
+    #[test]
+    fn level_one_monsters_stay_near_their_base_stats() {
+        for seed in 0..50 {
+            let m = generate_monster(1, &mut seeded(seed));
+            assert!((m.def.hp..=m.def.hp + 5).contains(&m.hp), "{} has {} HP", m.def.name, m.hp);
+            assert!((m.def.attack..=m.def.attack + 1).contains(&m.attack), "{} has {} attack", m.def.name, m.attack);
+            assert_eq!(m.hp, m.max_hp);
+        }
+    }
+
+    #[test]
+    fn monster_turns_replay_exactly_with_the_same_seed() {
+        for name in monsters::catalog().iter().map(|m| m.name.as_str()) {
+            let fight = |seed| {
+                let mut rng = seeded(seed);
+                let mut player = hero();
+                let mut foe = monster(name, 10, 40);
+                let mut conditions = Conditions::default();
+                let mut fled = false;
+                for _ in 0..10 {
+                    fled = monster_turn(&mut foe, &mut player, &mut conditions, &mut rng).fled;
+                    if fled || player.current_hp <= 0 {
+                        break;
+                    }
+                    conditions.tick(&mut player);
+                }
+                (player.current_hp, player.poison, foe.hp, fled, conditions)
+            };
+            assert_eq!(fight(7), fight(7), "{} fought differently", name);
+        }
+    }
+
+    #[test]
+    fn a_fleeing_monster_does_not_strike() {
+        let mut flights = 0;
+        for seed in 0..100 {
+            let mut player = hero();
+            let mut boar = monster("Wild Boar", 1, 100);
+            if monster_turn(&mut boar, &mut player, &mut Conditions::default(), &mut seeded(seed)).fled {
+                flights += 1;
+                assert_eq!(player.current_hp, player.max_hp);
+            } else {
+                assert!(player.current_hp < player.max_hp);
+            }
+        }
+        // The boar flees 30% of the time when badly wounded
+        assert!((10..=50).contains(&flights), "fled {} times", flights);
+    }
+
+    #[test]
+    fn unattended_fights_end_in_a_consistent_state() {
+        for seed in 0..50 {
+            let mut player = hero();
+            player.forest_fights = 5;
+            let outcome = fight_monster_unattended(&mut player, &mut seeded(seed));
+            assert_eq!(player.forest_fights, 4);
+            match outcome {
+                Unattended::Won => assert!(player.exp > 0 || player.level > 5),
+                Unattended::Fled => assert!(player.alive),
+                Unattended::Died => assert!(!player.alive && player.current_hp == 0),
+            }
+        }
+    }
+
+    #[test]
+    fn a_turn_spent_on_an_item_deals_no_damage() {
+        for seed in 0..20 {
+            let mut dragon = RedDragon { hp: RED_DRAGON_HP };
+            let mut player = hero();
+            let round = fight_round(&mut dragon, &mut player, 10, true, &mut Conditions::default(), &mut seeded(seed), &mut Vec::new());
+            assert_eq!(round, Round::Fighting);
+            assert_eq!(dragon.hp, RED_DRAGON_HP);
+            assert!(player.current_hp < player.max_hp);
+        }
+    }
+
+    #[test]
+    fn regeneration_never_exceeds_max_hp() {
+        for seed in 0..20 {
+            let mut ogre = monster("Ogre", 39, 40);
+            monster_turn(&mut ogre, &mut hero(), &mut Conditions::default(), &mut seeded(seed));
+            assert_eq!(ogre.hp, 40);
+        }
+    }
+}
@@ -0,0 +1,171 @@
+//! Gambling at the Dark Cloak Tavern: dice and Twenty-One.
+//!
+//! Both games are played against the house for a stake of the player's gold.
+//! The odds are fair, but the house keeps `house_edge_percent` of every win,
+//! and each hero may only wager `max_daily_wager` gold per game day. Every bet
+//! is recorded as an event for the lifetime statistics; wins of at least
+//! `big_win_gold` make the news.
+use colored::Colorize;
+use rand::Rng;
+use sqlx::PgPool;
+use crate::config::rules;
+use crate::db;
+use crate::game::rng::GameRng;
+use crate::game::{GameEvent, Player, save_player};
+use crate::ui::{self, session};
+
+/// How a round against the house ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Outcome {
+    Win,
+    Lose,
+    Push,
+}
+
+/// Sit down at the gaming tables until the player walks away.
+pub async fn visit_gaming_tables(conn: &PgPool, player: &mut Player, rng: &mut GameRng) {
+    println!("\nA crowd of rogues and merchants huddles around the gaming tables.");
+    while !session::expired() {
+        let left = rules().max_daily_wager - player.gold_wagered;
+        println!("\n=== Gaming Tables ===");
+        println!("You have {} gold. You may wager {} more gold today.", player.gold, left.max(0));
+        println!("1. Dice (highest roll of two dice wins)");
+        println!("2. Twenty-One (beat the dealer without going over 21)");
+        println!("3. Leave the tables");
+        let choice = ui::prompt("Choose a game: ");
+        let game = match choice.trim() {
+            "1" => "dice",
+            "2" => "twenty-one",
+            _ => return,
+        };
+        let Some(stake) = place_bet(player) else {
+            continue;
+        };
+        let outcome = if game == "dice" { play_dice(rng) } else { play_twenty_one(rng) };
+        settle(conn, player, game, stake, outcome).await;
+    }
+}
+
+/// Ask for a stake and check it against the player's purse and daily limit.
+fn place_bet(player: &Player) -> Option<i32> {
+    let left = rules().max_daily_wager - player.gold_wagered;
+    if left <= 0 {
+        println!("The house won't take any more of your bets today. Come back tomorrow.");
+        return None;
+    }
+    let input = ui::prompt("How much gold will you wager? ");
+    let stake = match input.trim().parse::<i32>() {
+        Ok(stake) if stake > 0 => stake,
+        _ => {
+            println!("You think better of it.");
+            return None;
+        }
+    };
+    if stake > player.gold {
+        println!("You don't have that much gold.");
+        None
+    } else if stake > left {
+        println!("The house won't take more than {} more gold from you today.", left);
+        None
+    } else {
+        Some(stake)
+    }
+}
+
+/// Roll two dice against the house; the higher total wins and a tie is a push.
+fn play_dice(rng: &mut GameRng) -> Outcome {
+    let mut roll = || rng.random_range(1..=6) + rng.random_range(1..=6);
+    let yours = roll();
+    let house = roll();
+    println!("You roll {}. The house rolls {}.", yours, house);
+    match yours.cmp(&house) {
+        std::cmp::Ordering::Greater => Outcome::Win,
+        std::cmp::Ordering::Less => Outcome::Lose,
+        std::cmp::Ordering::Equal => Outcome::Push,
+    }
+}
+
+/// Name of a card rank from 1 (ace) to 13 (king).
+fn card_name(rank: i32) -> String {
+    match rank {
+        1 => "A".to_string(),
+        11 => "J".to_string(),
+        12 => "Q".to_string(),
+        13 => "K".to_string(),
+        n => n.to_string(),
+    }
+}
+
+/// Best total of a Twenty-One hand, counting an ace as 11 when that does not bust.
+fn hand_value(hand: &[i32]) -> i32 {
+    let total: i32 = hand.iter().map(|&rank| rank.min(10)).sum();
+    if hand.contains(&1) && total + 10 <= 21 { total + 10 } else { total }
+}
+
+/// Show a hand and its value.
+fn show_hand(owner: &str, hand: &[i32]) {
+    let cards: Vec<String> = hand.iter().map(|&rank| card_name(rank)).collect();
+    println!("{} hand: {} ({})", owner, cards.join(" "), hand_value(hand));
+}
+
+/// Play a hand of Twenty-One against the dealer, who draws to 17.
+fn play_twenty_one(rng: &mut GameRng) -> Outcome {
+    let mut draw = || rng.random_range(1..=13);
+    let mut yours = vec![draw(), draw()];
+    let mut dealer = vec![draw()];
+    println!("The dealer shows {}.", card_name(dealer[0]));
+    show_hand("Your", &yours);
+    while hand_value(&yours) < 21 {
+        let choice = ui::prompt("(H)it or (S)tand? ");
+        if !choice.trim().eq_ignore_ascii_case("H") {
+            break;
+        }
+        yours.push(draw());
+        show_hand("Your", &yours);
+    }
+    let your_value = hand_value(&yours);
+    if your_value > 21 {
+        println!("You bust!");
+        return Outcome::Lose;
+    }
+    dealer.push(draw());
+    while hand_value(&dealer) < 17 {
+        dealer.push(draw());
+    }
+    show_hand("The dealer's", &dealer);
+    let dealer_value = hand_value(&dealer);
+    if dealer_value > 21 {
+        println!("The dealer busts!");
+        return Outcome::Win;
+    }
+    match your_value.cmp(&dealer_value) {
+        std::cmp::Ordering::Greater => Outcome::Win,
+        std::cmp::Ordering::Less => Outcome::Lose,
+        std::cmp::Ordering::Equal => Outcome::Push,
+    }
+}
+
+/// Pay out or collect a bet, less the house's cut of any win, and record it.
+async fn settle(conn: &PgPool, player: &mut Player, game: &str, stake: i32, outcome: Outcome) {
+    player.gold_wagered += stake;
+    let net = match outcome {
+        Outcome::Win => {
+            let winnings = (stake * (100 - rules().house_edge_percent) / 100).max(1);
+            println!("{}", format!("You win {} gold! (The house keeps its cut.)", winnings).bright_green());
+            winnings
+        }
+        Outcome::Lose => {
+            println!("{}", format!("You lose {} gold.", stake).red());
+            -stake
+        }
+        Outcome::Push => {
+            println!("It's a tie. Your stake is returned.");
+            0
+        }
+    };
+    player.gold += net;
+    if net != 0 {
+        db::log_event(conn, &GameEvent::gamble(player, game, net)).await.ok();
+    }
+    save_player(conn, player).await;
+}
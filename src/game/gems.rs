@@ -0,0 +1,83 @@
+//! Gems: the realm's second currency.
+//!
+//! Gems are found on the bodies of forest monsters and cannot be bought with
+//! gold. The bartender of the Dark Cloak Tavern trades them for permanent
+//! boosts to maximum hit points, strength or defense, giving heroes something
+//! to work towards once levels come easily. Gems and the boosts bought with
+//! them survive a Red Dragon rebirth but not a new season.
+use colored::Colorize;
+use rand::Rng;
+use sqlx::PgPool;
+use crate::config::rules;
+use crate::game::rng::GameRng;
+use crate::game::{Player, save_player};
+use crate::ui;
+
+/// Roll for gems on a slain forest monster, adding any found to the player.
+///
+/// # Returns
+///
+/// The number of gems found.
+pub fn roll_gems(player: &mut Player, rng: &mut GameRng) -> i32 {
+    if rng.random_range(0..100) >= rules().gem_find_chance {
+        return 0;
+    }
+    // Tougher heroes hunt tougher monsters, which now and then carry two
+    let found = if player.level >= 5 && rng.random_bool(0.25) { 2 } else { 1 };
+    player.gems += found;
+    found
+}
+
+/// Roll for gems on a slain forest monster and tell the player about any found.
+pub fn find_gems(player: &mut Player, monster: &str, rng: &mut GameRng) {
+    let found = roll_gems(player, rng);
+    if found == 0 {
+        return;
+    }
+    let what = if found == 1 { "a gem".to_string() } else { format!("{} gems", found) };
+    println!("{}", format!("You find {} on the {}'s body!", what, monster).bright_magenta());
+}
+
+/// Trade gems with the bartender for permanent stat boosts.
+pub async fn trade_with_bartender(conn: &PgPool, player: &mut Player) {
+    let rules = rules();
+    let price = rules.gem_trade_price;
+    println!("\nThe bartender leans over the counter. \"Got any gems for me, friend?\"");
+    loop {
+        println!("\nYou have {} gem(s). Each trade costs {} gem(s).", player.gems, price);
+        println!("1. Vitality (+{} max HP)", rules.gem_trade_max_hp);
+        println!("2. Strength (+1 attack)");
+        println!("3. Defense (+1 defense)");
+        println!("4. Never mind");
+        let choice = ui::prompt("What'll it be? ");
+        let choice = choice.trim();
+        if !matches!(choice, "1" | "2" | "3") {
+            println!("The bartender shrugs and goes back to polishing glasses.");
+            return;
+        }
+        if player.gems < price {
+            println!("\"Come back when you have {} gems,\" the bartender grunts.", price);
+            return;
+        }
+        player.gems -= price;
+        match choice {
+            "1" => {
+                player.max_hp += rules.gem_trade_max_hp;
+                player.gem_max_hp += rules.gem_trade_max_hp;
+                player.current_hp += rules.gem_trade_max_hp;
+                println!("{}", format!("You feel hardier! Your maximum HP rises to {}.", player.max_hp).bright_green());
+            }
+            "2" => {
+                player.attack += 1;
+                player.gem_attack += 1;
+                println!("{}", format!("You feel stronger! Your attack rises to {}.", player.attack).bright_green());
+            }
+            _ => {
+                player.defense += 1;
+                player.gem_defense += 1;
+                println!("{}", format!("Your skin toughens! Your defense rises to {}.", player.defense).bright_green());
+            }
+        }
+        save_player(conn, player).await;
+    }
+}
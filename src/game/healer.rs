@@ -0,0 +1,59 @@
+//! The Healer's Hut: mending wounds and curing poison for gold.
+use colored::Colorize;
+use sqlx::PgPool;
+use crate::config::rules;
+use crate::db;
+use crate::game::{GameEvent, Player, online, save_player};
+use crate::ui::{self, session};
+
+/// Charge the player for a treatment, recording the purchase.
+///
+/// # Returns
+///
+/// `false` if the player cannot afford it.
+async fn pay(conn: &PgPool, player: &mut Player, treatment: &str, price: i32) -> bool {
+    if player.gold < price {
+        println!("\"That will be {} gold, which you don't have,\" the healer says flatly.", price);
+        return false;
+    }
+    player.gold -= price;
+    db::log_event(conn, &GameEvent::purchase(player, treatment, price)).await.ok();
+    true
+}
+
+/// Visit the healer to restore hit points or cure poison.
+pub async fn visit_healer(conn: &PgPool, player: &mut Player) {
+    online::set_location(conn, player, "Healer's Hut").await;
+    println!("\nThe old healer looks up from her herbs. \"Who's hurt?\"");
+    while !session::expired() {
+        let wounds = player.max_hp - player.current_hp;
+        let heal_price = wounds * rules().healer_price_per_hp;
+        println!("\n=== Healer's Hut ===");
+        println!("HP: {}/{}  Gold: {}", player.current_hp, player.max_hp, player.gold);
+        if player.poison > 0 {
+            println!("{}", format!("You are poisoned ({} damage per round).", player.poison).magenta());
+        }
+        println!("1. Heal all wounds ({} gold)", heal_price);
+        println!("2. Cure poison ({} gold)", rules().cure_poison_price);
+        println!("3. Return to town");
+        match ui::prompt("Choose an option: ").trim() {
+            "1" if wounds == 0 => println!("\"You look healthy enough to me.\""),
+            "1" => {
+                if pay(conn, player, "healing", heal_price).await {
+                    player.current_hp = player.max_hp;
+                    println!("{}", "The healer's salves close your wounds. You are fully healed.".bright_green());
+                    save_player(conn, player).await;
+                }
+            }
+            "2" if player.poison == 0 => println!("\"There's no poison in you, friend.\""),
+            "2" => {
+                if pay(conn, player, "cure poison", rules().cure_poison_price).await {
+                    player.poison = 0;
+                    println!("{}", "The healer's bitter tonic purges the poison from your blood.".bright_green());
+                    save_player(conn, player).await;
+                }
+            }
+            _ => return,
+        }
+    }
+}
@@ -0,0 +1,153 @@
+//! Inventory: the stacks of items each hero carries.
+//!
+//! Items are found in the forest and used from the town or between rounds of
+//! a forest fight. What each item is and does comes from the item catalog
+//! (see the `items` module); the `inventory` table only records how many of
+//! each a hero carries.
+use colored::Colorize;
+use rand::Rng;
+use sqlx::PgPool;
+use crate::db;
+use crate::game::items::{self, ItemDef};
+use crate::game::rng::GameRng;
+use crate::game::{Player, reload_player};
+use crate::ui;
+
+/// The player's items, with their catalog entries, in catalog order.
+///
+/// Stacks of items no longer in the catalog are left out.
+async fn load(conn: &PgPool, player: &Player) -> Vec<(&'static ItemDef, i32)> {
+    let stacks = match db::get_inventory(conn, player.id).await {
+        Ok(stacks) => stacks,
+        Err(e) => {
+            println!("Could not open your pack: {}", e);
+            return Vec::new();
+        }
+    };
+    items::catalog()
+        .iter()
+        .filter_map(|item| {
+            let (_, quantity) = stacks.iter().find(|(id, _)| *id == item.id)?;
+            Some((item, *quantity))
+        })
+        .collect()
+}
+
+/// What became of an attempt to use an item.
+enum Used {
+    Done,
+    NoneLeft,
+    Conflict,
+}
+
+/// Use one of an item, taking it out of the pack and saving its effect together.
+async fn use_item(conn: &PgPool, player: &mut Player, item: &ItemDef) {
+    // The item leaves the pack in the same transaction that saves what it did,
+    // so a failed save keeps the item and a used item always has its effect
+    let before = player.clone();
+    player.time_used = ui::session::time_used_secs();
+    let message = item.apply(player);
+    let used = async {
+        let mut tx = conn.begin().await?;
+        if !db::remove_item(&mut tx, player.id, &item.id, 1).await? {
+            tx.rollback().await?;
+            return Ok(Used::NoneLeft);
+        }
+        if !db::write_player(&mut tx, player).await? {
+            tx.rollback().await?;
+            return Ok(Used::Conflict);
+        }
+        tx.commit().await?;
+        Ok::<Used, sqlx::Error>(Used::Done)
+    }.await;
+    match used {
+        Ok(Used::Done) => {
+            player.version += 1;
+            println!("{}", message.bright_green());
+        }
+        Ok(Used::NoneLeft) => {
+            println!("You rummage through your pack, but you have no {} left.", item.name);
+            *player = before;
+        }
+        Ok(Used::Conflict) => {
+            reload_player(conn, player).await;
+            println!("The {} is still in your pack.", item.name);
+        }
+        Err(e) => {
+            println!("Could not use the {}: {}", item.name, e);
+            *player = before;
+        }
+    }
+}
+
+/// Show the player's pack in town and let them use an item.
+pub async fn show_inventory(conn: &PgPool, player: &mut Player) {
+    let stacks = load(conn, player).await;
+    println!("\n{}", "Your Pack:".bright_white().bold());
+    if stacks.is_empty() {
+        println!("Your pack is empty.");
+        let _ = ui::prompt("Press Enter to continue...");
+        return;
+    }
+    for (i, (item, quantity)) in stacks.iter().enumerate() {
+        println!("{}. {} x{} - {}", i + 1, item.name, quantity, item.description);
+    }
+    let choice = ui::prompt("Enter a number to use that item, or press Enter to return: ");
+    let Some((item, _)) = choice.trim().parse::<usize>().ok()
+        .and_then(|n| n.checked_sub(1))
+        .and_then(|i| stacks.get(i))
+    else {
+        return;
+    };
+    if item.usable() {
+        use_item(conn, player, item).await;
+    } else {
+        println!("The {} is not something you can use.", item.name);
+    }
+}
+
+/// Whether the player carries anything usable in combat.
+pub async fn has_combat_items(conn: &PgPool, player: &Player) -> bool {
+    load(conn, player).await.iter().any(|(item, _)| item.combat && item.usable())
+}
+
+/// Let the player use an item between rounds of a fight.
+///
+/// # Returns
+///
+/// `true` if an item was used, which takes the player's turn.
+pub async fn use_in_combat(conn: &PgPool, player: &mut Player) -> bool {
+    let stacks: Vec<_> = load(conn, player).await
+        .into_iter()
+        .filter(|(item, _)| item.combat && item.usable())
+        .collect();
+    for (i, (item, quantity)) in stacks.iter().enumerate() {
+        println!("  {}. {} x{}", i + 1, item.name, quantity);
+    }
+    let choice = ui::prompt("Use which item? (Enter to keep fighting): ");
+    match choice.trim().parse::<usize>().ok().and_then(|n| n.checked_sub(1)).and_then(|i| stacks.get(i)) {
+        Some((item, _)) => {
+            use_item(conn, player, item).await;
+            true
+        }
+        None => false,
+    }
+}
+
+/// Roll for items dropped by a slain forest monster.
+pub fn roll_drops(player: &Player, rng: &mut GameRng) -> Vec<&'static ItemDef> {
+    items::catalog()
+        .iter()
+        .filter(|item| player.level >= item.min_level && rng.random::<f64>() * 100.0 < item.drop_chance)
+        .collect()
+}
+
+/// Put the items dropped by a slain forest monster in the player's pack.
+pub async fn keep_drops(conn: &PgPool, player: &Player, monster: &str, found: Vec<&ItemDef>) {
+    for item in found {
+        match db::add_item(conn, player.id, &item.id, 1).await {
+            Ok(()) => println!("{}", format!("The {} dropped a {}!", monster, item.name).bright_yellow()),
+            Err(e) => eprintln!("Failed to add item to inventory: {}", e),
+        }
+    }
+}
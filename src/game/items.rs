@@ -0,0 +1,199 @@
+//! Item catalog: every kind of item a hero can carry.
+//!
+//! Items are defined in data rather than code. The catalog shipped with the
+//! game is `data/items.toml`, built into the binary; a board can replace it by
+//! pointing the `LORD_ITEMS` environment variable at its own file. Players'
+//! stacks of items are kept by the `inventory` module.
+use serde::Deserialize;
+use std::env;
+use std::fs;
+use std::sync::OnceLock;
+use crate::config::{ConfigError, check_range};
+use crate::game::Player;
+
+/// The catalog shipped with the game.
+const DEFAULT_ITEMS: &str = include_str!("../../data/items.toml");
+
+/// The catalog loaded at startup.
+static CATALOG: OnceLock<Vec<ItemDef>> = OnceLock::new();
+
+/// Layout of the item catalog file.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct CatalogFile {
+    item: Vec<ItemDef>,
+}
+
+/// One kind of item.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ItemDef {
+    /// Stable identifier stored in the database
+    pub id: String,
+    /// Name shown to players
+    pub name: String,
+    /// One-line description shown in the inventory
+    pub description: String,
+    /// What using the item does
+    #[serde(default)]
+    pub effect: Effect,
+    /// Whether the item can be used between rounds of a forest fight
+    #[serde(default)]
+    pub combat: bool,
+    /// Percent chance that a slain forest monster drops the item
+    #[serde(default)]
+    pub drop_chance: f64,
+    /// Lowest hero level that finds the item in the forest
+    #[serde(default)]
+    pub min_level: i32,
+}
+
+/// The effect of using an item.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub enum Effect {
+    /// The item cannot be used
+    #[default]
+    None,
+    /// Restore a percentage of maximum hit points
+    Heal { percent: i32 },
+    /// Permanently raise attack
+    Attack { amount: i32 },
+    /// Permanently raise defense
+    Defense { amount: i32 },
+    /// Permanently raise maximum hit points
+    MaxHp { amount: i32 },
+    /// Cure poison
+    Cure,
+}
+
+impl ItemDef {
+    /// Whether the item does anything when used.
+    pub fn usable(&self) -> bool {
+        self.effect != Effect::None
+    }
+
+    /// Check that the item's numbers are ones the game can use.
+    ///
+    /// Effects must help the hero (a heal of 1 to 100 percent, a raise of at
+    /// least 1), the drop chance must lie between 0 and 100, and the level at
+    /// which it starts dropping can't be negative.
+    fn validate(&self) -> Result<(), String> {
+        let field = |name: &str| format!("item '{}': {}", self.id, name);
+        match self.effect {
+            Effect::Heal { percent } => check_range(&field("heal percent"), percent, 1, 100)?,
+            Effect::Attack { amount } => check_range(&field("attack amount"), amount, 1, i32::MAX)?,
+            Effect::Defense { amount } => check_range(&field("defense amount"), amount, 1, i32::MAX)?,
+            Effect::MaxHp { amount } => check_range(&field("max_hp amount"), amount, 1, i32::MAX)?,
+            Effect::None | Effect::Cure => {}
+        }
+        if !(0.0..=100.0).contains(&self.drop_chance) {
+            return Err(format!("{} must be between 0 and 100 (got {})", field("drop_chance"), self.drop_chance));
+        }
+        check_range(&field("min_level"), self.min_level, 0, i32::MAX)
+    }
+
+    /// Apply the item's effect to the player and describe what happened.
+    pub fn apply(&self, player: &mut Player) -> String {
+        match self.effect {
+            Effect::None => format!("Nothing happens. The {} is not something you can use.", self.name),
+            Effect::Heal { percent } => {
+                let old_hp = player.current_hp;
+                let heal = (player.max_hp * percent / 100).max(1);
+                player.current_hp = (player.current_hp + heal).min(player.max_hp);
+                format!("You drink the {}. It restores {} HP.", self.name, player.current_hp - old_hp)
+            }
+            Effect::Attack { amount } => {
+                player.attack += amount;
+                format!("You feel stronger! Your attack rises to {}.", player.attack)
+            }
+            Effect::Defense { amount } => {
+                player.defense += amount;
+                format!("Your skin toughens! Your defense rises to {}.", player.defense)
+            }
+            Effect::MaxHp { amount } => {
+                player.max_hp += amount;
+                player.current_hp += amount;
+                format!("Vitality surges through you! Your maximum HP rises to {}.", player.max_hp)
+            }
+            Effect::Cure if player.poison == 0 => format!("You drink the {}. It tastes awful, but you were not poisoned.", self.name),
+            Effect::Cure => {
+                player.poison = 0;
+                format!("You drink the {}. The poison leaves your blood.", self.name)
+            }
+        }
+    }
+}
+
+/// Load the item catalog and make it available through `catalog`.
+///
+/// # Errors
+///
+/// Returns a `ConfigError` if `LORD_ITEMS` names a file that cannot be read, or
+/// the catalog is not valid, repeats an item id, or has an item with
+/// out-of-range numbers.
+pub fn load() -> Result<(), ConfigError> {
+    let (path, text) = match env::var("LORD_ITEMS") {
+        Ok(path) => {
+            let text = fs::read_to_string(&path).map_err(|e| ConfigError::Io(path.clone(), e))?;
+            (path, text)
+        }
+        Err(_) => ("data/items.toml".to_string(), DEFAULT_ITEMS.to_string()),
+    };
+    let items = parse(&path, &text)?;
+    CATALOG.get_or_init(|| items);
+    Ok(())
+}
+
+/// Parse and check an item catalog read from `path`.
+fn parse(path: &str, text: &str) -> Result<Vec<ItemDef>, ConfigError> {
+    let file: CatalogFile = toml::from_str(text).map_err(|e| ConfigError::Parse(path.to_string(), e))?;
+    for (i, item) in file.item.iter().enumerate() {
+        item.validate().map_err(|e| ConfigError::Invalid(path.to_string(), e))?;
+        // Inventories refer to items by id, so two items can't share one
+        if file.item[..i].iter().any(|other| other.id == item.id) {
+            return Err(ConfigError::Invalid(path.to_string(), format!("item id '{}' is used more than once", item.id)));
+        }
+    }
+    Ok(file.item)
+}
+
+/// Every item in the catalog.
+pub fn catalog() -> &'static [ItemDef] {
+    CATALOG.get_or_init(|| {
+        toml::from_str::<CatalogFile>(DEFAULT_ITEMS)
+            .map(|file| file.item)
+            .unwrap_or_default()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A catalog entry for a potion with the given extra settings.
+    fn potion(id: &str, extra: &str) -> String {
+        format!("[[item]]\nid = \"{}\"\nname = \"Potion\"\ndescription = \"A potion.\"\n{}\n", id, extra)
+    }
+
+    #[test]
+    fn the_shipped_catalog_is_valid() {
+        assert!(!parse("data/items.toml", DEFAULT_ITEMS).expect("valid catalog").is_empty());
+    }
+
+    #[test]
+    fn out_of_range_and_duplicate_items_are_rejected() {
+        assert!(parse("test.toml", &potion("potion", "effect = { type = \"heal\", percent = 50 }\ndrop_chance = 5.0")).is_ok());
+        for (text, problem) in [
+            (potion("potion", "effect = { type = \"heal\", percent = -10 }"), "item 'potion': heal percent must be at least 1 (got -10)"),
+            (potion("potion", "effect = { type = \"attack\", amount = -2 }"), "item 'potion': attack amount must be at least 1 (got -2)"),
+            (potion("potion", "drop_chance = 150.0"), "item 'potion': drop_chance must be between 0 and 100 (got 150)"),
+            (potion("potion", "") + &potion("potion", ""), "item id 'potion' is used more than once"),
+        ] {
+            match parse("test.toml", &text) {
+                Err(ConfigError::Invalid(path, e)) => assert_eq!((path.as_str(), e.as_str()), ("test.toml", problem)),
+                other => panic!("accepted a bad item ({}): {:?}", problem, other.map(|_| ())),
+            }
+        }
+    }
+}
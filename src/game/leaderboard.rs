@@ -0,0 +1,103 @@
+//! Leaderboards: ranked listings of every hero in the realm.
+//!
+//! Like the player listing of the original game, each board shows every hero
+//! with their level and whether they are alive, dead or online right now, a
+//! page at a time. Boards rank by experience, wealth, duels won, PvP rating,
+//! dragon kills or account age, and any hero on a board can be looked up by rank.
+use colored::Colorize;
+use sqlx::PgPool;
+use crate::db::{self, Leaderboard, LeaderboardEntry};
+use crate::game::profile;
+use crate::ui::{self, session};
+
+/// Number of heroes shown per page.
+const PAGE_SIZE: i64 = 15;
+
+/// Let the player browse the leaderboards, starting with the Hall of Fame.
+///
+/// Commands:
+/// - `N` / `P`: next or previous page
+/// - `B`: switch to another board
+/// - a rank number: view that hero's profile
+/// - `Q`: return to town
+pub async fn show_leaderboards(conn: &PgPool) {
+    let mut board = Leaderboard::Experience;
+    let mut page = 0;
+    while !session::expired() {
+        let (entries, total) = match (
+            db::get_leaderboard(conn, board, page, PAGE_SIZE).await,
+            db::count_players(conn).await,
+        ) {
+            (Ok(entries), Ok(total)) => (entries, total),
+            (Err(e), _) | (_, Err(e)) => {
+                println!("Could not retrieve leaderboard: {}", e);
+                return;
+            }
+        };
+        let pages = ((total + PAGE_SIZE - 1) / PAGE_SIZE).max(1);
+
+        println!("\n{} (page {} of {})", board.label().bright_yellow().bold(), page + 1, pages);
+        if entries.is_empty() {
+            println!("No players to display.");
+        } else {
+            println!("{:<6}{:<22}{:<7}{:<10}Status", "Rank", "Name", "Level", board.score_label());
+            for (i, entry) in entries.iter().enumerate() {
+                let rank = page * PAGE_SIZE + i as i64 + 1;
+                println!("{:<6}{:<22}{:<7}{:<10}{}", rank, entry.name, entry.level, entry.score, status(entry));
+            }
+        }
+
+        println!("\n(N)ext  (P)revious  (B)oard  rank number = profile  (Q)uit");
+        let choice = ui::prompt("> ");
+        let choice = choice.trim().to_uppercase();
+        match choice.as_str() {
+            "N" if page + 1 < pages => page += 1,
+            "N" => println!("This is the last page."),
+            "P" if page > 0 => page -= 1,
+            "P" => println!("This is the first page."),
+            "B" => {
+                board = choose_board().unwrap_or(board);
+                page = 0;
+            }
+            "Q" | "" => return,
+            _ => match choice.parse::<i64>() {
+                Ok(rank) if (1..=total).contains(&rank) => {
+                    // Ranks on other pages are fetched on demand
+                    match db::get_leaderboard(conn, board, rank - 1, 1).await {
+                        Ok(found) if !found.is_empty() => {
+                            profile::show_profile(conn, found[0].id).await;
+                            let _ = ui::prompt("Press Enter to continue...");
+                        }
+                        Ok(_) => println!("No hero holds that rank."),
+                        Err(e) => println!("Could not retrieve leaderboard: {}", e),
+                    }
+                }
+                _ => println!("Invalid choice."),
+            },
+        }
+    }
+}
+
+/// The status column of the listing: online, alive or dead.
+fn status(entry: &LeaderboardEntry) -> String {
+    if entry.online {
+        "Online".bright_cyan().to_string()
+    } else if entry.alive {
+        "Alive".green().to_string()
+    } else {
+        "Dead".red().to_string()
+    }
+}
+
+/// Ask which board to show; `None` keeps the current one.
+fn choose_board() -> Option<Leaderboard> {
+    println!();
+    for (i, board) in Leaderboard::ALL.iter().enumerate() {
+        println!("{}. {}", i + 1, board.label());
+    }
+    let choice = ui::prompt("Show which board? ");
+    match choice.trim().parse::<usize>() {
+        Ok(n) if (1..=Leaderboard::ALL.len()).contains(&n) => Some(Leaderboard::ALL[n - 1]),
+        _ => None,
+    }
+}
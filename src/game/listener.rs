@@ -0,0 +1,98 @@
+//! Live delivery of database notifications to a session.
+//!
+//! Features that reach other nodes (chat, breaking news) store their rows in a
+//! table and announce them with a Postgres `NOTIFY` on their own channel. Each
+//! session runs one `Listener`, on a single dedicated connection, for all of
+//! those channels. Every feature contributes a `Feed` saying how to fetch its
+//! rows; when a notification arrives, the feed for its channel fetches
+//! everything newer than the last row it delivered and the lines meant for the
+//! player are queued on the session, so they appear before the next prompt.
+use sqlx::PgPool;
+use sqlx::postgres::PgListener;
+use std::future::Future;
+use std::pin::Pin;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use crate::ui::session::Session;
+
+/// Rows newer than a feed's last seen id, each as its id and the line to show the player.
+type Rows = Result<Vec<(i32, Option<String>)>, sqlx::Error>;
+
+/// Fetches a feed's rows newer than the given id.
+type Fetch = Box<dyn Fn(PgPool, i32) -> Pin<Box<dyn Future<Output = Rows> + Send>> + Send>;
+
+/// One notification channel and how to fetch what it announces.
+pub struct Feed {
+    channel: &'static str,
+    /// Names the feature in error messages
+    what: &'static str,
+    last_seen: i32,
+    fetch: Fetch,
+}
+
+impl Feed {
+    /// A feed for `channel`, delivering rows newer than `last_seen`.
+    ///
+    /// `fetch` is given the id of the last row seen and returns the newer rows
+    /// in order, each as its id and the line to show the player (`None` for
+    /// rows the player shouldn't see). `what` names the feature in error messages.
+    pub fn new<F, Fut>(channel: &'static str, what: &'static str, last_seen: i32, fetch: F) -> Feed
+    where
+        F: Fn(PgPool, i32) -> Fut + Send + 'static,
+        Fut: Future<Output = Rows> + Send + 'static,
+    {
+        Feed { channel, what, last_seen, fetch: Box::new(move |conn, after| Box::pin(fetch(conn, after))) }
+    }
+
+    /// Fetch the rows announced since the last delivery and queue them on `session`.
+    async fn deliver(&mut self, conn: &PgPool, session: &Session) {
+        match (self.fetch)(conn.clone(), self.last_seen).await {
+            Ok(rows) => {
+                for (id, line) in rows {
+                    self.last_seen = id;
+                    if let Some(line) = line {
+                        session.notify(line);
+                    }
+                }
+            }
+            Err(e) => eprintln!("Failed to fetch {}: {}", self.what, e),
+        }
+    }
+}
+
+/// Background task delivering every feed's notifications to a session.
+pub struct Listener {
+    task: JoinHandle<()>,
+}
+
+impl Listener {
+    /// Listen on every feed's channel over one connection, queueing what they deliver on `session`.
+    pub async fn start(conn: &PgPool, session: Session, mut feeds: Vec<Feed>) -> Result<Listener, sqlx::Error> {
+        let mut listener = PgListener::connect_with(conn).await?;
+        listener.listen_all(feeds.iter().map(|feed| feed.channel)).await?;
+        let conn = conn.clone();
+        let task = tokio::spawn(async move {
+            loop {
+                // The listener reconnects on its own; anything missed meanwhile
+                // is picked up with the next notification since we fetch by id
+                let notification = match listener.recv().await {
+                    Ok(notification) => notification,
+                    Err(e) => {
+                        eprintln!("Lost the notification connection: {}", e);
+                        tokio::time::sleep(Duration::from_secs(1)).await;
+                        continue;
+                    }
+                };
+                if let Some(feed) = feeds.iter_mut().find(|feed| feed.channel == notification.channel()) {
+                    feed.deliver(&conn, &session).await;
+                }
+            }
+        });
+        Ok(Listener { task })
+    }
+
+    /// Stop delivering notifications.
+    pub fn stop(self) {
+        self.task.abort();
+    }
+}
@@ -6,6 +6,7 @@
 //! ## Features
 //!
 //! - Player data structure with stats, inventory, and state  
+//! - Rules-driven pacing loaded from the configuration file  
 //! - Experience and leveling system  
 //! - Combat mechanics for PvE and PvP  
 //! - Social interactions and romance options  
@@ -13,28 +14,86 @@
 //! ## Implementation Details
 //!
 //! The game module is divided into several submodules:  
+//! - `bounty`: Lets players post gold bounties on each other's heads  
+//! - `buffs`: Grants temporary effects that last until the daily reset  
+//! - `calendar`: Computes game days and schedules the daily maintenance  
+//! - `chat`: Delivers live messages between nodes  
+//! - `events`: Describes game events, recorded for statistics and rendered into the news  
 //! - `forest`: Handles monster encounters and combat in the forest  
+//! - `gambling`: Runs the dice and card games at the tavern  
+//! - `gems`: Handles gem finds and the bartender's gem trade  
+//! - `healer`: Heals wounds and cures poison for gold  
+//! - `inventory`: Keeps the items each hero carries and lets them use them  
+//! - `items`: Loads the catalog of items from data  
+//! - `leaderboard`: Ranks every hero by experience, wealth, duels, dragon kills or age  
+//! - `listener`: Delivers database notifications to the running session  
+//! - `monsters`: Loads the catalog of forest monsters from data  
+//! - `news`: Shows the daily news and announces breaking news as it happens  
+//! - `online`: Tracks who is online and keeps a player from being logged in twice  
+//! - `profile`: Shows lifetime statistics and player profiles  
 //! - `pvp`: Manages player-vs-player duels and rankings  
+//! - `rng`: Provides the seedable random number generator behind every roll  
 //! - `romance`: Implements NPC and player romance options  
+//! - `season`: Archives finished seasons and starts fresh ones  
+//! - `status`: Tracks poison, stun and bleeding in combat  
 //! - `town`: Provides the main game menu and hub functionality  
 
+/// Bounty board and escrowed bounties module
+pub mod bounty;
+/// Temporary daily effects module
+pub mod buffs;
+/// Game-day boundaries and daily maintenance scheduling module
+pub mod calendar;
+/// Live inter-node chat module
+pub mod chat;
+/// Structured game events module
+pub mod events;
 /// Forest exploration and monster combat module
 pub mod forest;
+/// Tavern gambling games module
+pub mod gambling;
+/// Gem finds and gem trading module
+pub mod gems;
+/// Healer's Hut module
+pub mod healer;
+/// Player inventory module
+pub mod inventory;
+/// Item catalog module
+pub mod items;
+/// Leaderboards and player listing module
+pub mod leaderboard;
+/// Database notification listener module
+pub mod listener;
+/// Monster catalog module
+pub mod monsters;
+/// Daily news reader and live breaking news module
+pub mod news;
+/// Online presence, session locks and heartbeats module
+pub mod online;
+/// Lifetime statistics and player profiles module
+pub mod profile;
 /// Player versus player combat module
 pub mod pvp;
+/// Seedable game random number generator module
+pub mod rng;
 /// NPC and player romance interactions module
 pub mod romance;
+/// Season rollover and champion announcements module
+pub mod season;
+/// Balance simulation of headless heroes fighting in the forest
+pub mod simulate;
+/// Combat status effects module
+pub mod status;
 /// Town hub and main menu module
 pub mod town;
 
 use sqlx::PgPool;
 use chrono::NaiveDateTime;
 use colored::Colorize;
+use crate::config::rules;
 use crate::db;
 
-/// Maximum number of forest fights per player per day.
-/// This constant limits how many monsters a player can fight each day.
-pub const MAX_DAILY_FOREST_FIGHTS: i32 = 10;
+pub use events::GameEvent;
 
 /// Player data structure representing a player's state in the game.
 /// 
@@ -42,10 +101,12 @@ pub const MAX_DAILY_FOREST_FIGHTS: i32 = 10;
 /// - Basic identification (id, name)
 /// - Authentication data (password)
 /// - Character stats (level, hp, attack, defense)
-/// - Game progress (exp, gold, forest_fights)
+/// - Game progress (exp, gold, forest_fights, player_fights)
 /// - Social status (alive, romance, spouse)
-/// - Session data (last_login)
-#[derive(Debug, Clone, PartialEq, Eq, sqlx::FromRow)]
+/// - Session data (last_login, time_used)
+/// - Achievements (dragon_kills, rating)
+/// - Concurrency control (version)
+#[derive(Debug, Clone, Default, PartialEq, Eq, sqlx::FromRow)]
 pub struct Player {
     /// Unique player identifier
     pub id: i32,
@@ -59,6 +120,18 @@ pub struct Player {
     pub exp: i32,
     /// Gold pieces carried
     pub gold: i32,
+    /// Gems carried, traded at the tavern for permanent stat boosts
+    pub gems: i32,
+    /// Maximum HP bought with gems, kept through a Red Dragon rebirth
+    pub gem_max_hp: i32,
+    /// Attack bought with gems, kept through a Red Dragon rebirth
+    pub gem_attack: i32,
+    /// Defense bought with gems, kept through a Red Dragon rebirth
+    pub gem_defense: i32,
+    /// Gold wagered at the tavern's gaming tables today
+    pub gold_wagered: i32,
+    /// Damage per combat round from poison (0 when healthy)
+    pub poison: i32,
     /// Current hit points
     pub current_hp: i32,
     /// Maximum hit points
@@ -69,6 +142,8 @@ pub struct Player {
     pub defense: i32,
     /// Remaining forest fights for the day
     pub forest_fights: i32,
+    /// Remaining duels the player may start today
+    pub player_fights: i32,
     /// Whether the player is alive or dead
     pub alive: bool,
     /// Romance points with Violet (NPC)
@@ -77,19 +152,49 @@ pub struct Player {
     pub spouse: String,
     /// Timestamp of last login
     pub last_login: NaiveDateTime,
+    /// Number of times this hero has slain the Red Dragon this season
+    pub dragon_kills: i32,
+    /// PvP rating, updated after every duel (Elo)
+    pub rating: i32,
+    /// When the character was created (unknown for characters older than the record)
+    pub created_at: Option<NaiveDateTime>,
+    /// Seconds played during the current game day
+    pub time_used: i32,
+    /// Row version, incremented on every save to detect conflicting writes
+    pub version: i32,
 }
 
 impl Player {
     /// Calculate the experience points required for the next level.
     /// 
-    /// The formula is simple: level * 100
+    /// The formula is simple: level * `xp_per_level` (100 by default)
     /// This means higher levels require more experience to advance.
     /// 
     /// # Returns
     /// 
     /// The amount of experience points needed to reach the next level.
     pub fn xp_to_next_level(&self) -> i32 {
-        self.level * 100
+        self.level * rules().xp_per_level
+    }
+
+    /// Return the character to the stats of a freshly created hero.
+    ///
+    /// Name, password, marriage, gems and dragon kills are kept, and so are the
+    /// boosts bought with gems, which are added to the starting stats; everything
+    /// else that describes the hero's progress is wiped. This is what happens to
+    /// a hero after slaying the Red Dragon.
+    pub fn reset_to_starting_stats(&mut self) {
+        let rules = rules();
+        self.level = 1;
+        self.exp = 0;
+        self.gold = rules.starting_gold;
+        self.max_hp = rules.starting_hp + self.gem_max_hp;
+        self.current_hp = self.max_hp;
+        self.attack = rules.starting_attack + self.gem_attack;
+        self.defense = rules.starting_defense + self.gem_defense;
+        self.forest_fights = rules.max_daily_forest_fights;
+        self.poison = 0;
+        self.alive = true;
     }
 }
 
@@ -98,7 +203,7 @@ impl Player {
 /// This function:
 /// 1. Checks if the player has enough XP to level up
 /// 2. Deducts the required XP and increases level
-/// 3. Improves player stats (HP, attack, defense)
+/// 3. Improves player stats (HP, attack, defense) by the configured level-up gains
 /// 4. Announces the level-up to the player
 /// 5. Logs the achievement in the game news
 /// 
@@ -110,21 +215,77 @@ impl Player {
 /// * `player` - Mutable reference to the player being checked for level-up
 /// * `conn` - Database connection pool for logging the event
 pub async fn try_level_up(player: &mut Player, conn: &PgPool) {
-    while player.exp >= player.xp_to_next_level() {
-        player.exp -= player.xp_to_next_level();
-        player.level += 1;
-        // Increase stats upon leveling up
-        player.max_hp += 10;
-        player.current_hp = player.max_hp;
-        player.attack += 2;
-        player.defense += 1;
+    while gain_level(player) {
         println!("{}", format!("Congratulations! You are now Level {}.", player.level).bright_green().bold());
         // Log the level-up event in the news
-        let news = format!("{} has reached Level {}!", player.name, player.level);
-        let news_owned = news.to_string();
+        let event = GameEvent::level_up(player);
         let conn = conn.clone();
         tokio::spawn(async move {
-            db::log_event(&conn, &news_owned).await.ok();
+            db::log_event(&conn, &event).await.ok();
         });
     }
 }
+
+/// Advance the player one level if they have enough experience, applying the
+/// configured level-up gains and restoring them to full health.
+///
+/// # Returns
+///
+/// `true` if the player gained a level.
+pub fn gain_level(player: &mut Player) -> bool {
+    if player.exp < player.xp_to_next_level() {
+        return false;
+    }
+    let rules = rules();
+    player.exp -= player.xp_to_next_level();
+    player.level += 1;
+    // Increase stats upon leveling up
+    player.max_hp += rules.level_up_hp;
+    player.current_hp = player.max_hp;
+    player.attack += rules.level_up_attack;
+    player.defense += rules.level_up_defense;
+    true
+}
+
+/// Save the player's progress, reporting any problem to them.
+///
+/// Game modules call this after every action that changes the player (each
+/// fight, purchase, or romance step), so a dropped connection or crash loses
+/// at most the action in progress. The time played so far this session is
+/// included in every save.
+///
+/// The database is the source of truth: if the player's record was changed
+/// elsewhere since it was loaded (for example a new season began or the daily
+/// reset ran mid-session), the save is rejected and the in-memory player is
+/// replaced by the stored one, with the daily clock restarted from its play time.
+///
+/// # Returns
+///
+/// `true` if the player was saved.
+pub async fn save_player(conn: &PgPool, player: &mut Player) -> bool {
+    player.time_used = crate::ui::session::time_used_secs();
+    match db::update_player(conn, player).await {
+        Ok(true) => true,
+        Ok(false) => {
+            reload_player(conn, player).await;
+            false
+        }
+        Err(e) => {
+            println!("Failed to save player data: {}", e);
+            false
+        }
+    }
+}
+
+/// Replace the in-memory player with the stored record after a version conflict.
+pub async fn reload_player(conn: &PgPool, player: &mut Player) {
+    println!("Your hero was changed elsewhere while you played; continuing from the stored record.");
+    match db::get_player_by_id(conn, player.id).await {
+        Ok(Some(stored)) => {
+            *player = stored;
+            crate::ui::session::start_daily_clock(player.time_used);
+        }
+        Ok(None) => println!("Could not find your hero in the database."),
+        Err(e) => println!("Error retrieving player: {}", e),
+    }
+}
@@ -0,0 +1,183 @@
+//! Monster catalog: every monster that can be met in the forest.
+//!
+//! Like the items, monsters are defined in data rather than code. The catalog
+//! shipped with the game is `data/monsters.toml`, built into the binary; a
+//! board can replace it by pointing the `LORD_MONSTERS` environment variable at
+//! its own file. Each monster has its own weapon, attack verb and death line,
+//! and may inflict a status effect or have special abilities.
+use serde::Deserialize;
+use std::env;
+use std::fs;
+use std::sync::OnceLock;
+use crate::config::{ConfigError, check_range};
+use crate::game::status::Status;
+
+/// The catalog shipped with the game.
+const DEFAULT_MONSTERS: &str = include_str!("../../data/monsters.toml");
+
+/// The catalog loaded at startup.
+static CATALOG: OnceLock<Vec<MonsterDef>> = OnceLock::new();
+
+/// Layout of the monster catalog file.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct CatalogFile {
+    monster: Vec<MonsterDef>,
+}
+
+/// One kind of monster, with its stats for a Level 1 hero.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MonsterDef {
+    /// Name shown to players
+    pub name: String,
+    /// Base hit points
+    pub hp: i32,
+    /// Base attack power
+    pub attack: i32,
+    /// What the monster hits with
+    pub weapon: String,
+    /// How the monster hits ("stabs", "bites")
+    pub verb: String,
+    /// Line shown when the monster is slain
+    pub death: String,
+    /// Status effect the monster's hits may inflict
+    #[serde(default)]
+    pub status: Option<Affliction>,
+    /// Special behaviors
+    #[serde(default)]
+    pub abilities: Vec<Ability>,
+}
+
+/// A status effect a monster's hits may inflict.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Affliction {
+    /// The status effect
+    pub effect: Status,
+    /// Percent chance per hit
+    pub chance: i32,
+}
+
+/// A monster's special behavior in combat.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub enum Ability {
+    /// Percent chance to strike twice in a round
+    DoubleStrike { chance: i32 },
+    /// Heal a percentage of maximum hit points every round
+    Regenerate { percent: i32 },
+    /// Heal a percentage of the damage dealt
+    LifeDrain { percent: i32 },
+    /// Percent chance each round to flee once below `below` percent of maximum hit points
+    Flee { chance: i32, below: i32 },
+}
+
+impl MonsterDef {
+    /// Check that the monster's numbers are ones the forest can fight with.
+    ///
+    /// A monster needs at least 1 HP and 1 attack to roll its damage and
+    /// rewards, and every chance or percentage must lie between 0 and 100.
+    fn validate(&self) -> Result<(), String> {
+        let field = |name: &str| format!("monster '{}': {}", self.name, name);
+        check_range(&field("hp"), self.hp, 1, i32::MAX)?;
+        check_range(&field("attack"), self.attack, 1, i32::MAX)?;
+        if let Some(affliction) = self.status {
+            check_range(&field("status chance"), affliction.chance, 0, 100)?;
+        }
+        for ability in &self.abilities {
+            match *ability {
+                Ability::DoubleStrike { chance } => check_range(&field("double_strike chance"), chance, 0, 100)?,
+                Ability::Regenerate { percent } => check_range(&field("regenerate percent"), percent, 0, 100)?,
+                Ability::LifeDrain { percent } => check_range(&field("life_drain percent"), percent, 0, 100)?,
+                Ability::Flee { chance, below } => {
+                    check_range(&field("flee chance"), chance, 0, 100)?;
+                    check_range(&field("flee below"), below, 0, 100)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// The percentage of damage dealt the monster heals, if it drains life.
+    pub fn life_drain(&self) -> Option<i32> {
+        self.abilities.iter().find_map(|ability| match *ability {
+            Ability::LifeDrain { percent } => Some(percent),
+            _ => None,
+        })
+    }
+}
+
+/// Load the monster catalog and make it available through `catalog`.
+///
+/// # Errors
+///
+/// Returns a `ConfigError` if `LORD_MONSTERS` names a file that cannot be read,
+/// or the catalog is not valid, is empty, or has a monster with out-of-range numbers.
+pub fn load() -> Result<(), ConfigError> {
+    let (path, text) = match env::var("LORD_MONSTERS") {
+        Ok(path) => {
+            let text = fs::read_to_string(&path).map_err(|e| ConfigError::Io(path.clone(), e))?;
+            (path, text)
+        }
+        Err(_) => ("data/monsters.toml".to_string(), DEFAULT_MONSTERS.to_string()),
+    };
+    let monsters = parse(&path, &text)?;
+    CATALOG.get_or_init(|| monsters);
+    Ok(())
+}
+
+/// Parse and check a monster catalog read from `path`.
+fn parse(path: &str, text: &str) -> Result<Vec<MonsterDef>, ConfigError> {
+    let file: CatalogFile = toml::from_str(text).map_err(|e| ConfigError::Parse(path.to_string(), e))?;
+    if file.monster.is_empty() {
+        return Err(ConfigError::Parse(path.to_string(), serde::de::Error::custom("the catalog defines no monsters")));
+    }
+    for monster in &file.monster {
+        monster.validate().map_err(|e| ConfigError::Invalid(path.to_string(), e))?;
+    }
+    Ok(file.monster)
+}
+
+/// Every monster in the catalog.
+pub fn catalog() -> &'static [MonsterDef] {
+    CATALOG.get_or_init(|| {
+        toml::from_str::<CatalogFile>(DEFAULT_MONSTERS)
+            .map(|file| file.monster)
+            .unwrap_or_default()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_shipped_catalog_is_valid() {
+        assert!(!parse("data/monsters.toml", DEFAULT_MONSTERS).expect("valid catalog").is_empty());
+    }
+
+    /// A catalog holding one mole with the given stats and extra settings.
+    fn mole(hp: i32, attack: i32, extra: &str) -> String {
+        format!(
+            "[[monster]]\nname = \"Mole\"\nhp = {}\nattack = {}\nweapon = \"claws\"\nverb = \"scratches\"\ndeath = \"The mole dies.\"\n{}\n",
+            hp, attack, extra
+        )
+    }
+
+    #[test]
+    fn out_of_range_monsters_are_rejected() {
+        assert!(parse("test.toml", &mole(5, 2, "")).is_ok());
+        for (text, problem) in [
+            (mole(0, 2, ""), "monster 'Mole': hp must be at least 1 (got 0)"),
+            (mole(5, 0, ""), "monster 'Mole': attack must be at least 1 (got 0)"),
+            (mole(5, 2, "status = { effect = \"poison\", chance = 101 }"), "monster 'Mole': status chance must be at most 100 (got 101)"),
+            (mole(5, 2, "abilities = [{ type = \"flee\", chance = 30, below = -1 }]"), "monster 'Mole': flee below must be at least 0 (got -1)"),
+        ] {
+            match parse("test.toml", &text) {
+                Err(ConfigError::Invalid(path, e)) => assert_eq!((path.as_str(), e.as_str()), ("test.toml", problem)),
+                other => panic!("accepted a bad monster ({}): {:?}", problem, other.map(|_| ())),
+            }
+        }
+    }
+}
@@ -0,0 +1,130 @@
+//! The daily news: reading it page by page, and breaking news delivered live
+//! to every online session.
+//!
+//! News entries are grouped by game day under "Today's News", "Yesterday's
+//! News" and older dated headings, like the paper in the original game. Players
+//! can page back through history, narrow the paper to one category (duels,
+//! deaths, ...) and show only the events they were involved in.
+//!
+//! `db::log_event` announces each new entry in the `news` table with a
+//! Postgres `NOTIFY` on the `lord_news` channel. Every session's `Listener`
+//! queues the new entries from the news `Feed`, so a dragon slain on one node
+//! is announced on all the others before their next prompt.
+use chrono::{NaiveDate, Utc};
+use colored::Colorize;
+use sqlx::PgPool;
+use crate::db::{self, NewsFilter, NewsKind};
+use crate::game::{Player, calendar};
+use crate::game::listener::Feed;
+use crate::ui::{self, session};
+
+/// Number of news entries shown per page.
+const PAGE_SIZE: i64 = 15;
+
+/// Postgres notification channel announcing new news entries.
+pub const NEWS_CHANNEL: &str = "lord_news";
+
+/// The feed of breaking news logged from now on.
+///
+/// News involving the player is skipped; they saw it happen.
+pub async fn feed(conn: &PgPool, player_id: i32) -> Result<Feed, sqlx::Error> {
+    let last_seen = db::latest_news_id(conn).await?;
+    Ok(Feed::new(NEWS_CHANNEL, "breaking news", last_seen, move |conn, after| async move {
+        let entries = db::get_news_since(&conn, after).await?;
+        Ok(entries
+            .into_iter()
+            .map(|entry| {
+                let seen = entry.actor_id == Some(player_id) || entry.target_id == Some(player_id);
+                let line = (!seen).then(|| format!("[Breaking News] {}", entry.message).bright_yellow().to_string());
+                (entry.id, line)
+            })
+            .collect())
+    }))
+}
+
+/// Let the player read the daily news, newest page first.
+///
+/// Commands:
+/// - `O` / `N`: page to older or newer entries
+/// - `C`: show only one category, or all of them again
+/// - `M`: toggle between everyone's news and only the events involving the player
+/// - `Q`: return to town
+pub async fn read_news(conn: &PgPool, player: &Player) {
+    let mut filter = NewsFilter::default();
+    let mut page = 0;
+    while !session::expired() {
+        let (entries, total) = match (
+            db::get_news_page(conn, &filter, page, PAGE_SIZE).await,
+            db::count_news(conn, &filter).await,
+        ) {
+            (Ok(entries), Ok(total)) => (entries, total),
+            (Err(e), _) | (_, Err(e)) => {
+                println!("Error loading news: {}", e);
+                return;
+            }
+        };
+        let pages = ((total + PAGE_SIZE - 1) / PAGE_SIZE).max(1);
+
+        println!("\n{}", "Daily News Bulletin".bright_yellow().bold());
+        let kind = filter.kind.map_or("All news", NewsKind::label);
+        let whose = if filter.involving.is_some() { "involving you" } else { "from the whole realm" };
+        println!("{} {} (page {} of {})", kind, whose, page + 1, pages);
+
+        if entries.is_empty() {
+            println!("No news to report.");
+        }
+        let today = calendar::game_date(Utc::now());
+        let mut current_day = None;
+        for entry in &entries {
+            if current_day != Some(entry.game_day) {
+                current_day = Some(entry.game_day);
+                println!("\n{}", day_heading(entry.game_day, today).bright_white().bold());
+            }
+            println!("[{}] {}", entry.date.format("%H:%M"), entry.message);
+        }
+
+        println!("\n(O)lder  (N)ewer  (C)ategory  (M)ine/everyone  (Q)uit");
+        let choice = ui::prompt("> ");
+        match choice.trim().to_uppercase().as_str() {
+            "O" if page + 1 < pages => page += 1,
+            "O" => println!("There is no older news."),
+            "N" if page > 0 => page -= 1,
+            "N" => println!("You are already reading the latest news."),
+            "C" => {
+                filter.kind = choose_category();
+                page = 0;
+            }
+            "M" => {
+                filter.involving = match filter.involving {
+                    Some(_) => None,
+                    None => Some(player.id),
+                };
+                page = 0;
+            }
+            "Q" | "" => return,
+            _ => println!("Invalid choice."),
+        }
+    }
+}
+
+/// Heading for the news of `day`, in the style of the original paper.
+fn day_heading(day: NaiveDate, today: NaiveDate) -> String {
+    match (today - day).num_days() {
+        0 => "Today's News".to_string(),
+        1 => "Yesterday's News".to_string(),
+        _ => format!("News from {}", day.format("%A, %B %-d")),
+    }
+}
+
+/// Ask which category of news to show; `None` means all categories.
+fn choose_category() -> Option<NewsKind> {
+    println!("\n0. All news");
+    for (i, kind) in NewsKind::ALL.iter().enumerate() {
+        println!("{}. {}", i + 1, kind.label());
+    }
+    let choice = ui::prompt("Show which news? ");
+    match choice.trim().parse::<usize>() {
+        Ok(n) if (1..=NewsKind::ALL.len()).contains(&n) => Some(NewsKind::ALL[n - 1]),
+        _ => None,
+    }
+}
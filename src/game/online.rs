@@ -0,0 +1,105 @@
+//! Online presence: who is playing, on which node, and where in the realm.
+//!
+//! A session claims the player's lock at login and keeps it fresh with a
+//! heartbeat in the background, so only one session can play a hero at a time.
+//! Locks whose heartbeat stops (a crashed node or dropped connection) go stale
+//! after `lock_timeout_seconds` and can then be claimed by a new login.
+//!
+//! The lock row doubles as the presence registry: it records the session's
+//! node number and current location for the "Who's Online" list. Because it
+//! lives in the database, sessions see each other whether they run in the same
+//! process or on different machines.
+use rand::Rng;
+use sqlx::PgPool;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use crate::config;
+use crate::db;
+use crate::game::Player;
+use crate::ui::session::Session;
+
+/// The online lock held by the running session.
+pub struct SessionLock {
+    player_id: i32,
+    token: String,
+    heartbeat: JoinHandle<()>,
+}
+
+impl SessionLock {
+    /// Try to mark the player as online.
+    ///
+    /// If another session claims the player, the heartbeat expires `session`.
+    ///
+    /// # Returns
+    ///
+    /// `Ok(Some(lock))` if this session now owns the player, `Ok(None)` if the
+    /// player is already playing elsewhere, or a `sqlx::Error` if the database fails.
+    pub async fn acquire(conn: &PgPool, player_id: i32, session: Session) -> Result<Option<SessionLock>, sqlx::Error> {
+        let timeout = config::get().session.lock_timeout_seconds.max(1);
+        let token = format!("{:016x}", rand::rng().random::<u64>());
+        if !db::acquire_session_lock(conn, player_id, &token, timeout).await? {
+            return Ok(None);
+        }
+
+        // Renew the lock well within the timeout so it never looks stale while we play
+        let interval = Duration::from_secs((timeout as u64 / 4).max(1));
+        let heartbeat = {
+            let conn = conn.clone();
+            let token = token.clone();
+            tokio::spawn(async move {
+                loop {
+                    tokio::time::sleep(interval).await;
+                    match db::renew_session_lock(&conn, player_id, &token).await {
+                        Ok(true) => {}
+                        Ok(false) => {
+                            session.expire("Your hero has been claimed by another session. Ending this one...");
+                            break;
+                        }
+                        Err(e) => eprintln!("Failed to renew session lock: {}", e),
+                    }
+                }
+            })
+        };
+        Ok(Some(SessionLock { player_id, token, heartbeat }))
+    }
+
+    /// Stop the heartbeat and mark the player as offline.
+    pub async fn release(self, conn: &PgPool) {
+        self.heartbeat.abort();
+        if let Err(e) = db::release_session_lock(conn, self.player_id, &self.token).await {
+            eprintln!("Failed to release session lock: {}", e);
+        }
+    }
+}
+
+/// Record where in the realm the player is, for the "Who's Online" list.
+///
+/// Failures are ignored: a stale location is not worth interrupting play for.
+pub async fn set_location(conn: &PgPool, player: &Player, location: &str) {
+    db::set_player_location(conn, player.id, location).await.ok();
+}
+
+/// Show everyone who is currently playing, with their node and location.
+pub async fn show_whos_online(conn: &PgPool) {
+    println!("\nWho's Online:");
+    match db::list_online_players(conn).await {
+        Ok(list) => {
+            if list.is_empty() {
+                println!("Nobody is in the realm right now.");
+            } else {
+                println!("{:<6}{:<22}{:<8}{:<16}Since", "Node", "Name", "Level", "Location");
+                for online in list {
+                    println!(
+                        "{:<6}{:<22}{:<8}{:<16}{}",
+                        online.node,
+                        online.name,
+                        online.level,
+                        online.location,
+                        online.started_at.format("%H:%M")
+                    );
+                }
+            }
+        }
+        Err(e) => println!("Could not retrieve the list of players online: {}", e),
+    }
+}
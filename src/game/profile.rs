@@ -0,0 +1,65 @@
+//! Lifetime statistics and player profiles.
+//!
+//! Statistics are kept in `player_stats` and `player_monster_kills`, updated
+//! from the game events as they are recorded, and survive season rollovers.
+//! A profile shows any hero's current standing together with their record.
+use colored::Colorize;
+use sqlx::PgPool;
+use crate::db;
+
+/// Number of monster types listed on a profile.
+const TOP_MONSTERS: usize = 5;
+
+/// Show a player's lifetime record.
+pub async fn show_lifetime_stats(conn: &PgPool, player_id: i32) {
+    let stats = match db::get_player_stats(conn, player_id).await {
+        Ok(stats) => stats,
+        Err(e) => {
+            println!("Could not retrieve lifetime statistics: {}", e);
+            return;
+        }
+    };
+    println!("\n{}", "Lifetime Record:".bright_white().bold());
+    println!("Highest level reached: {}", stats.highest_level);
+    println!("Days played: {}", stats.days_played);
+    println!("Monsters slain: {}  Deaths: {}", stats.monsters_killed, stats.deaths);
+    println!("Duels won: {}  Duels lost: {}", stats.duels_won, stats.duels_lost);
+    println!("Gold earned: {}  Gold spent: {}", stats.gold_earned, stats.gold_spent);
+    println!("Red Dragons slain: {}", stats.dragon_kills);
+
+    match db::get_monster_kills(conn, player_id).await {
+        Ok(kills) if !kills.is_empty() => {
+            println!("Favourite prey:");
+            for (monster, count) in kills.iter().take(TOP_MONSTERS) {
+                println!("  {:<20}{}", monster, count);
+            }
+        }
+        Ok(_) => {}
+        Err(e) => println!("Could not retrieve monster kills: {}", e),
+    }
+}
+
+/// Show the profile of any player: their current standing and lifetime record.
+pub async fn show_profile(conn: &PgPool, player_id: i32) {
+    let player = match db::get_player_by_id(conn, player_id).await {
+        Ok(Some(player)) => player,
+        Ok(None) => {
+            println!("That hero is no longer in the realm.");
+            return;
+        }
+        Err(e) => {
+            println!("Error retrieving player: {}", e);
+            return;
+        }
+    };
+    println!("\n{}", format!("Profile of {}", player.name).bright_yellow().bold());
+    println!("Level: {}  Status: {}", player.level, if player.alive { "Alive" } else { "Dead" });
+    println!("Attack: {}  Defense: {}  Max HP: {}", player.attack, player.defense, player.max_hp);
+    if player.spouse.is_empty() {
+        println!("Spouse: (none)");
+    } else {
+        println!("Spouse: {}", player.spouse);
+    }
+    println!("Red Dragon kills this season: {}  PvP rating: {}", player.dragon_kills, player.rating);
+    show_lifetime_stats(conn, player_id).await;
+}
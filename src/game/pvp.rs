@@ -1,12 +1,34 @@
 //! Player vs Player combat: choosing an opponent and simulating a duel.
+//!
+//! To keep strong heroes from farming new ones, a hero may only challenge
+//! opponents within `duel_level_range` levels below them, and the spoils of
+//! beating a weaker hero shrink with the level gap. Every duel also moves both
+//! heroes' PvP rating using the Elo system.
+//!
+//! Heroes may start only `max_daily_player_fights` duels per day, and newly
+//! created characters cannot be challenged for `new_player_protection_hours`.
+//!
+//! Duels deliberately leave out stun and bleeding. Only monster blows inflict
+//! them, and they last only for the fight in progress, so nothing in a duel
+//! can cause them. Poison is stored on the hero, so a hero who arrives poisoned
+//! suffers it every round of the duel.
 use rand::Rng;
 use colored::Colorize;
 use crate::ui::ansi_art; // Import ANSI art
-use crate::game::Player;
+use crate::config::rules;
+use crate::game::buffs::{self, Buff};
+use crate::game::rng::GameRng;
+use crate::game::status::Conditions;
+use crate::game::{GameEvent, Player, online, save_player};
 use crate::db;
 use sqlx::PgPool;
 
-pub async fn challenge_player(conn: &PgPool, player: &mut Player) {
+pub async fn challenge_player(conn: &PgPool, player: &mut Player, rng: &mut GameRng) {
+    if player.player_fights <= 0 {
+        println!("You have no player fights left today. Come back tomorrow!");
+        return;
+    }
+    online::set_location(conn, player, "Arena").await;
     println!("{}", ansi_art::DUEL_SCENE.replace("{player_name}", &player.name)
                                        .replace("{opponent_name}", "Unknown Opponent"));
     // List potential opponents (alive players other than the current player)
@@ -17,11 +39,18 @@ pub async fn challenge_player(conn: &PgPool, player: &mut Player) {
             return;
         }
     };
+    // Heroes far below the player's level are protected from them
+    let min_level = min_challenge_level(player.level);
+    let total = opponents.len();
+    let opponents: Vec<_> = opponents.into_iter().filter(|opp| opp.level >= min_level).collect();
+    if opponents.len() < total {
+        println!("Heroes below Level {} are beneath your notice.", min_level);
+    }
     if opponents.is_empty() {
         println!("No other heroes are currently available to duel.");
         return;
     }
-    println!("\nWho would you like to challenge?");
+    println!("\nWho would you like to challenge? ({} player fights left today)", player.player_fights);
     for (idx, opp) in opponents.iter().enumerate() {
         println!("  {}. {} (Level {})", idx + 1, opp.name, opp.level);
     }
@@ -60,110 +89,165 @@ pub async fn challenge_player(conn: &PgPool, player: &mut Player) {
         println!("{} is not available to fight.", target.name);
         return;
     }
+    if target.level < min_level {
+        println!("{} is too inexperienced to be worth your blade.", target.name);
+        return;
+    }
+    match db::is_player_protected(conn, target.id).await {
+        Ok(false) => {}
+        Ok(true) => {
+            println!("{} has only just arrived in the realm and is under the town's protection.", target.name);
+            return;
+        }
+        Err(e) => {
+            println!("Error retrieving player: {}", e);
+            return;
+        }
+    }
+    // Heroes who are online right now would overwrite the outcome when they save
+    match db::is_player_online(conn, target.id).await {
+        Ok(false) => {}
+        Ok(true) => {
+            println!("{} is wandering the realm right now and cannot be challenged.", target.name);
+            return;
+        }
+        Err(e) => {
+            println!("Error retrieving player: {}", e);
+            return;
+        }
+    }
+    // Kept so the duel can be undone if its results cannot be saved
+    let before_duel = player.clone();
+    player.player_fights -= 1;
     println!("\nYou challenge {} to a duel!", target.name);
     println!("{} draws their weapon...", target.name);
-    // Simulate the duel
-    let mut rng = rand::rng();
-    while player.alive && target.alive {
+    // Simulate the duel, with each hero's strength buffs
+    let player_attack = player.attack + buffs::amount(conn, player.id, Buff::Strength).await;
+    let target_attack = target.attack + buffs::amount(conn, target.id, Buff::Strength).await;
+    let mut player_conditions = Conditions::default();
+    let mut target_conditions = Conditions::default();
+    // Fight until one of the heroes falls
+    while player.current_hp > 0 && target.current_hp > 0 {
+        // Poison burns each hero at the start of their turn; no hero can stun
+        // or wound another into bleeding, so the conditions only carry poison
+        let suffered = player_conditions.tick(player);
+        if suffered > 0 {
+            println!("{}", format!("Poison burns you for {} damage.", suffered).magenta());
+            if player.current_hp <= 0 {
+                break;
+            }
+        }
         // Player (attacker) strikes first
-        let dmg_to_target = rng.random_range(1..=player.attack);
+        let dmg_to_target = rng.random_range(1..=player_attack);
         target.current_hp -= dmg_to_target;
         println!("You hit {} for {} damage.", target.name, dmg_to_target);
         if target.current_hp <= 0 {
-            target.alive = false;
-            target.current_hp = 0;
-            println!("{}", format!("You have defeated {}!", target.name).bright_green().bold());
-            // Loot and reward
-            let stolen_gold = target.gold / 2;
-            if stolen_gold > 0 {
-                target.gold -= stolen_gold;
-                player.gold += stolen_gold;
-                println!("You loot {} gold from {}.", stolen_gold, target.name);
-            }
-            let xp_gain = target.level * 50;
-            if xp_gain > 0 {
-                player.exp += xp_gain;
-                println!("You gain {} experience from the victory!", xp_gain);
-                crate::game::try_level_up(player, conn).await;
-            }
-            // Log PvP victory
-            let news = format!("{} defeated {} in a duel!", player.name, target.name);
-            if let Err(e) = db::log_event(conn, &news).await {
-                eprintln!("Failed to log event: {}", e);
-            }
             break;
         }
+        let suffered = target_conditions.tick(&mut target);
+        if suffered > 0 {
+            println!("Poison burns {} for {} damage.", target.name, suffered);
+            if target.current_hp <= 0 {
+                break;
+            }
+        }
         // Opponent strikes back if still alive
-        let dmg_to_player = rng.random_range(1..=target.attack);
+        let dmg_to_player = rng.random_range(1..=target_attack);
         player.current_hp -= dmg_to_player;
         println!("{} hits you for {} damage.", target.name, dmg_to_player);
-        if player.current_hp <= 0 {
-            player.alive = false;
-            player.current_hp = 0;
-            println!("{}", "You have been defeated in combat...".bright_red().bold());
-            // When player dies in PvP, opponent might loot
-            let stolen_gold = player.gold / 2;
-            if stolen_gold > 0 {
-                player.gold -= stolen_gold;
-                target.gold += stolen_gold;
-            }
-            // Log PvP loss
-            let news = format!("{} was killed by {} in a duel!", player.name, target.name);
-            if let Err(e) = db::log_event(conn, &news).await {
-                eprintln!("Failed to log event: {}", e);
-            }
-            break;
-        }
-        // Loop continues until one is defeated
     }
+    let event = if target.current_hp <= 0 {
+        target.alive = false;
+        target.current_hp = 0;
+        println!("{}", format!("You have defeated {}!", target.name).bright_green().bold());
+        // Loot and reward, reduced for beating a weaker hero
+        let percent = reward_percent(player.level, target.level);
+        if percent < 100 {
+            println!("There is little glory in defeating a weaker hero.");
+        }
+        let stolen_gold = target.gold * rules().duel_loot_percent / 100 * percent / 100;
+        if stolen_gold > 0 {
+            target.gold -= stolen_gold;
+            player.gold += stolen_gold;
+            println!("You loot {} gold from {}.", stolen_gold, target.name);
+        }
+        let xp_gain = target.level * rules().duel_xp_per_level * percent / 100;
+        let change = rating_change(player.rating, target.rating);
+        player.rating += change;
+        target.rating -= change;
+        println!("Your PvP rating rises to {} (+{}).", player.rating, change);
+        // PvP victory is logged together with the results
+        let event = GameEvent::duel(player, &target, player.id, stolen_gold, xp_gain, change);
+        if xp_gain > 0 {
+            player.exp += xp_gain;
+            println!("You gain {} experience from the victory!", xp_gain);
+        }
+        event
+    } else {
+        player.alive = false;
+        player.current_hp = 0;
+        println!("{}", "You have been defeated in combat...".bright_red().bold());
+        // When player dies in PvP, opponent might loot
+        let stolen_gold = player.gold * rules().duel_loot_percent / 100 * reward_percent(target.level, player.level) / 100;
+        if stolen_gold > 0 {
+            player.gold -= stolen_gold;
+            target.gold += stolen_gold;
+        }
+        let change = rating_change(target.rating, player.rating);
+        target.rating += change;
+        player.rating -= change;
+        println!("Your PvP rating falls to {} (-{}).", player.rating, change);
+        // PvP loss is logged together with the results
+        GameEvent::duel(&target, player, player.id, stolen_gold, 0, change)
+    };
 
     // Update both players in the database after the duel
-    if let Err(e) = (async {
+    // Both writes are versioned: if either hero changed since they were loaded,
+    // the duel is called off and nothing is saved, not even its record
+    let saved = async {
         let mut tx = conn.begin().await?;
-        sqlx::query(
-            r#"UPDATE players SET
-                level=$1, exp=$2, gold=$3, current_hp=$4, max_hp=$5,
-                attack=$6, defense=$7, forest_fights=$8, alive=$9,
-                romance=$10, spouse=$11, last_login=NOW()
-            WHERE id=$12"#
-        )
-        .bind(player.level)
-        .bind(player.exp)
-        .bind(player.gold)
-        .bind(player.current_hp)
-        .bind(player.max_hp)
-        .bind(player.attack)
-        .bind(player.defense)
-        .bind(player.forest_fights)
-        .bind(player.alive)
-        .bind(player.romance)
-        .bind(&player.spouse)
-        .bind(player.id)
-        .execute(&mut *tx).await?;
-        sqlx::query(
-            r#"UPDATE players SET
-                level=$1, exp=$2, gold=$3, current_hp=$4, max_hp=$5,
-                attack=$6, defense=$7, forest_fights=$8, alive=$9,
-                romance=$10, spouse=$11, last_login=NOW()
-            WHERE id=$12"#
-        )
-        .bind(target.level)
-        .bind(target.exp)
-        .bind(target.gold)
-        .bind(target.current_hp)
-        .bind(target.max_hp)
-        .bind(target.attack)
-        .bind(target.defense)
-        .bind(target.forest_fights)
-        .bind(target.alive)
-        .bind(target.romance)
-        .bind(&target.spouse)
-        .bind(target.id)
-        .execute(&mut *tx).await?;
+        // Any bounties on the loser's head go to the winner
+        let (winner, loser) = if player.alive { (&mut *player, &target) } else { (&mut target, &*player) };
+        let bounty = db::claim_bounties(&mut tx, loser.id, winner.id).await?;
+        winner.gold += bounty;
+        let bounty_event = (bounty > 0).then(|| GameEvent::bounty_claimed(winner, loser, bounty));
+        if !db::write_player(&mut tx, player).await? || !db::write_player(&mut tx, &target).await? {
+            tx.rollback().await?;
+            return Ok(None);
+        }
+        for event in std::iter::once(&event).chain(&bounty_event) {
+            db::insert_event(&mut tx, event).await?;
+        }
         tx.commit().await?;
-        Ok::<(), sqlx::Error>(())
-    }).await {
-        println!("Failed to save duel results: {}", e);
+        Ok::<Option<i32>, sqlx::Error>(Some(bounty))
+    }.await;
+    match saved {
+        Ok(Some(bounty)) => {
+            player.version += 1;
+            if bounty > 0 && player.alive {
+                println!("{}", format!("You collect the {} gold bounty on {}'s head!", bounty, target.name).bright_yellow());
+            } else if bounty > 0 {
+                println!("{} collects the {} gold bounty on your head.", target.name, bounty);
+            }
+            // Level up only once the victory is on record, so a called-off duel announces nothing
+            if player.alive && player.exp >= player.xp_to_next_level() {
+                crate::game::try_level_up(player, conn).await;
+                save_player(conn, player).await;
+            }
+        }
+        Ok(None) => {
+            println!("{} was called away before the duel could be settled. It never happened.", target.name);
+            *player = before_duel;
+            let _ = crate::ui::prompt("Press Enter to continue...");
+            return;
+        }
+        Err(e) => {
+            // Nothing was saved, so nothing the duel gave or took may be kept
+            println!("Failed to save duel results: {}. The duel is called off.", e);
+            *player = before_duel;
+            let _ = crate::ui::prompt("Press Enter to continue...");
+            return;
+        }
     }
     if !player.alive {
         // If the current player died, they can't continue acting this day
@@ -174,3 +258,29 @@ pub async fn challenge_player(conn: &PgPool, player: &mut Player) {
     // Pause to allow player to see the outcome
     let _ = crate::ui::prompt("Press Enter to continue...");
 }
+
+/// Lowest level a hero of `level` may challenge, per the `duel_level_range` rule.
+fn min_challenge_level(level: i32) -> i32 {
+    match rules().duel_level_range {
+        range if range > 0 => level - range,
+        _ => i32::MIN,
+    }
+}
+
+/// Percentage of the usual duel rewards earned for beating an opponent.
+///
+/// Winners lose `duel_weaker_penalty_percent` for every level the loser is
+/// below them; beating an equal or stronger hero earns the full reward.
+fn reward_percent(winner_level: i32, loser_level: i32) -> i32 {
+    let gap = (winner_level - loser_level).max(0);
+    (100 - gap * rules().duel_weaker_penalty_percent).clamp(0, 100)
+}
+
+/// Rating points the winner of a duel takes from the loser (Elo).
+///
+/// An upset against a higher-rated hero moves up to `rating_k_factor` points;
+/// beating a much lower-rated hero moves almost none.
+fn rating_change(winner_rating: i32, loser_rating: i32) -> i32 {
+    let expected = 1.0 / (1.0 + 10f64.powf(f64::from(loser_rating - winner_rating) / 400.0));
+    (f64::from(rules().rating_k_factor) * (1.0 - expected)).round() as i32
+}
@@ -0,0 +1,87 @@
+//! The random number generator behind every roll of the dice.
+//!
+//! All game randomness (monsters, combat, drops, the bard, the gaming tables)
+//! is drawn from a single `GameRng` per session, passed down to the code that
+//! needs it rather than taken from the thread. Seeding it with `--seed` or the
+//! `[session] seed` setting makes a session's dice reproducible with the same
+//! build and the same inputs, so a reported bug can be replayed exactly and
+//! combat can be unit-tested.
+//!
+//! A recorded session (see `ui::recording`) also logs every draw, one number
+//! per line, so a replay can point at the first roll that came out differently.
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use rand::rngs::StdRng;
+use rand::{RngCore, SeedableRng};
+
+/// The generator used for game randomness.
+pub struct GameRng {
+    inner: StdRng,
+    /// Where each draw is written when the session is being recorded
+    log: Option<BufWriter<File>>,
+}
+
+impl GameRng {
+    /// Write a draw to the log, if there is one.
+    fn record(&mut self, draw: u64) {
+        if let Some(log) = &mut self.log {
+            let _ = writeln!(log, "{}", draw);
+        }
+    }
+}
+
+impl RngCore for GameRng {
+    fn next_u32(&mut self) -> u32 {
+        let draw = self.inner.next_u32();
+        self.record(u64::from(draw));
+        draw
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        let draw = self.inner.next_u64();
+        self.record(draw);
+        draw
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.inner.fill_bytes(dest);
+        for &byte in dest.iter() {
+            self.record(u64::from(byte));
+        }
+    }
+}
+
+/// Create a generator that always produces the same rolls for the same seed.
+pub fn seeded(seed: u64) -> GameRng {
+    GameRng { inner: StdRng::seed_from_u64(seed), log: None }
+}
+
+/// Create a seeded generator that writes every draw to the file at `path`.
+pub fn logged(seed: u64, path: &str) -> io::Result<GameRng> {
+    let log = BufWriter::new(File::create(path)?);
+    Ok(GameRng { inner: StdRng::seed_from_u64(seed), log: Some(log) })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::Rng;
+
+    #[test]
+    fn same_seed_gives_same_rolls() {
+        let mut a = seeded(42);
+        let mut b = seeded(42);
+        let rolls_a: Vec<i32> = (0..20).map(|_| a.random_range(1..=100)).collect();
+        let rolls_b: Vec<i32> = (0..20).map(|_| b.random_range(1..=100)).collect();
+        assert_eq!(rolls_a, rolls_b);
+    }
+
+    #[test]
+    fn different_seeds_give_different_rolls() {
+        let rolls = |seed| {
+            let mut rng = seeded(seed);
+            (0..20).map(|_| rng.random_range(1..=100)).collect::<Vec<i32>>()
+        };
+        assert_ne!(rolls(1), rolls(2));
+    }
+}
@@ -10,6 +10,9 @@
 //! - Marriage mechanics and benefits  
 //! - Gossip and news reading  
 //! - Drink purchasing for health restoration  
+//! - Gem trading with the bartender  
+//! - Dice and card games at the gaming tables  
+//! - Daily buffs from the bard's songs and from time with Violet  
 //!
 //! ## Implementation Details
 //!
@@ -19,8 +22,11 @@
 //! Marriage provides certain gameplay benefits.
 
 use colored::Colorize;
-use crate::ui::ansi_art; // Import ANSI art
-use crate::game::Player;
+use crate::ui::{ansi_art, session}; // Import ANSI art
+use crate::config::rules;
+use crate::game::buffs::{self, Buff};
+use crate::game::rng::GameRng;
+use crate::game::{GameEvent, Player, gambling, gems, online, save_player};
 use crate::db;
 use sqlx::PgPool;
 
@@ -38,14 +44,14 @@ use sqlx::PgPool;
 /// * `player` - Mutable reference to the player's data structure
 /// * `conn` - Database connection pool for logging events
 async fn flirt_with_violet(player: &mut Player, conn: &PgPool) {
-    // Check if already married to Violet
+    // Married heroes get a daily send-off instead
     if player.spouse.to_lowercase() == "violet" {
-        println!("Violet laughs, \"We're already married, dear!\"");
+        buffs::time_with_violet(conn, player).await;
         return;
     }
     
-    // Increase romance points
-    player.romance += 1;
+    // Increase romance points, more so for a charming hero
+    player.romance += 1 + buffs::amount(conn, player.id, Buff::Charm).await;
     
     // Different responses based on romance level
     let responses = [
@@ -74,11 +80,10 @@ async fn flirt_with_violet(player: &mut Player, conn: &PgPool) {
         println!("{}", "You and Violet are now married! The tavern erupts in cheers.".magenta().bold());
         
         // Log the marriage event
-        let news = format!("{} has married Violet, the tavern barmaid!", player.name);
-        let news_owned = news.to_string();
+        let event = GameEvent::marriage(player, "Violet");
         let conn = conn.clone();
         tokio::spawn(async move {
-            db::log_event(&conn, &news_owned).await.ok();
+            db::log_event(&conn, &event).await.ok();
         });
     }
 }
@@ -96,19 +101,23 @@ async fn flirt_with_violet(player: &mut Player, conn: &PgPool) {
 /// 
 /// * `conn` - Database connection pool for persistence operations
 /// * `player` - Mutable reference to the player's data structure
-pub async fn visit_tavern(conn: &PgPool, player: &mut Player) {
+pub async fn visit_tavern(conn: &PgPool, player: &mut Player, rng: &mut GameRng) {
+    online::set_location(conn, player, "Tavern").await;
     // Display the tavern scene
     println!("{}", ansi_art::TAVERN_SCENE);
     println!("\nYou enter the Dark Cloak Tavern. Violet greets you with a warm smile.");
     
-    // Tavern menu loop
-    loop {
+    // Tavern menu loop (ends early if the session runs out of time)
+    while !session::expired() {
         // Display tavern options
         println!("\n=== Tavern Options ===");
         println!("1. Flirt with Violet");
-        println!("2. Buy a drink (5 gold)");
+        println!("2. Buy a drink ({} gold)", rules().drink_price);
         println!("3. Listen to gossip (read daily news)");
-        println!("4. Return to town");
+        println!("4. Trade gems with the bartender");
+        println!("5. Gamble at the tables");
+        println!("6. Listen to the bard");
+        println!("7. Return to town");
         
         // Get player's choice
         let choice = crate::ui::prompt("Choose an option: ");
@@ -117,13 +126,16 @@ pub async fn visit_tavern(conn: &PgPool, player: &mut Player) {
             "1" => {
                 // Flirt with Violet
                 flirt_with_violet(player, conn).await;
+                save_player(conn, player).await;
             }
             "2" => {
                 // Buy a drink to restore health
-                if player.gold < 5 {
+                let price = rules().drink_price;
+                if player.gold < price {
                     println!("You don't have enough gold for a drink.");
                 } else {
-                    player.gold -= 5;
+                    player.gold -= price;
+                    db::log_event(conn, &GameEvent::purchase(player, "drink", price)).await.ok();
                     
                     // Calculate health restoration (25% of max HP)
                     let heal_amount = (player.max_hp / 4).max(1);
@@ -145,6 +157,7 @@ pub async fn visit_tavern(conn: &PgPool, player: &mut Player) {
                         
                         println!("The extra drink restores {} more HP.", player.current_hp - old_hp);
                     }
+                    save_player(conn, player).await;
                 }
             }
             "3" => {
@@ -167,10 +180,18 @@ pub async fn visit_tavern(conn: &PgPool, player: &mut Player) {
                     }
                 }
                 
-                println!("\nPress Enter to continue...");
-                let _ = std::io::stdin().read_line(&mut String::new());
+                let _ = crate::ui::prompt("\nPress Enter to continue...");
             }
             "4" => {
+                gems::trade_with_bartender(conn, player).await;
+            }
+            "5" => {
+                gambling::visit_gaming_tables(conn, player, rng).await;
+            }
+            "6" => {
+                buffs::listen_to_bard(conn, player, rng).await;
+            }
+            "7" => {
                 // Return to town
                 println!("You bid farewell to Violet and leave the tavern.");
                 return;
@@ -0,0 +1,66 @@
+//! Seasons: archiving the realm, crowning champions and starting over.
+//!
+//! A season ends either when an administrator runs the `new-season` command or,
+//! if the `season_dragon_kills` rule is set, when a single hero has slain the Red Dragon
+//! that many times.
+use colored::Colorize;
+use crate::config::rules;
+use crate::game::Player;
+use crate::db;
+use sqlx::PgPool;
+
+/// Close the current season, archive it and report the outcome.
+///
+/// # Returns
+///
+/// `true` if the season was rolled over, `false` if the database refused.
+pub async fn end_season(conn: &PgPool) -> bool {
+    match db::end_season(conn).await {
+        Ok(summary) => {
+            println!("{}", format!("Season {} has come to an end!", summary.season_id).yellow().bold());
+            println!("Played since {}.", summary.started_at.format("%Y-%m-%d"));
+            if summary.champions.is_empty() {
+                println!("No champion was crowned.");
+            } else {
+                println!("Champion(s): {}", summary.champions.join(", ").bright_green().bold());
+            }
+            println!(
+                "Archived {} heroes and {} news entries. Every hero starts anew.",
+                summary.archived_players, summary.archived_news
+            );
+            true
+        }
+        Err(e) => {
+            println!("Failed to start a new season: {}", e);
+            false
+        }
+    }
+}
+
+/// End the season if the player has slain the Red Dragon often enough.
+///
+/// The player is saved first so their final tally makes it into the archive,
+/// then reloaded afterwards so the session continues with the wiped character
+/// instead of overwriting it on exit.
+///
+/// # Arguments
+///
+/// * `conn` - Database connection pool
+/// * `player` - The hero who just slew the Red Dragon
+pub async fn check_dragon_rollover(conn: &PgPool, player: &mut Player) {
+    let limit = rules().season_dragon_kills;
+    if limit <= 0 || player.dragon_kills < limit {
+        return;
+    }
+    if !crate::game::save_player(conn, player).await {
+        return;
+    }
+    println!("\nYour legend is complete. The season draws to a close...");
+    if end_season(conn).await {
+        match db::get_player_by_id(conn, player.id).await {
+            Ok(Some(fresh)) => *player = fresh,
+            Ok(None) => println!("Could not find your hero after the rollover."),
+            Err(e) => println!("Error retrieving player: {}", e),
+        }
+    }
+}
@@ -0,0 +1,272 @@
+//! Balance simulation: many heroes fighting through many days in the forest.
+//!
+//! `simulate` plays headless heroes through game days of forest fights with
+//! the real monster generation, combat rounds, reward, gem and level-up rules
+//! (from the configuration file and the monster catalog), without a database
+//! or any output but a CSV report. Simulated heroes have no buffs or items.
+//! Each simulated day starts like a daily reset: the hero is revived and
+//! healed and gets a fresh allowance of forest fights. A hero who reaches
+//! `red_dragon_level` seeks out the Red Dragon, and one who slays it is reborn
+//! at Level 1 and carries on.
+//!
+//! How a hero spends the day is set by the strategy:
+//! - `reckless`: fight until out of forest fights or dead
+//! - `cautious`: go home for the day when HP falls below the threshold or
+//!   poison sets in
+//! - `healer`: pay the healer when HP falls below the threshold (and to cure
+//!   poison), going home only when the healer can't be afforded
+//!
+//! The report is written to stdout, so it can be redirected to a file:
+//! - `daily`: deaths, levels, gold and dragon kills across all heroes, per day
+//! - `levels`: how many days the heroes took to reach each level
+//! - `heroes`: where each hero ended up
+use crate::config::rules;
+use crate::game::forest::{self, Unattended};
+use crate::game::rng::GameRng;
+use crate::game::Player;
+
+/// How a simulated hero decides whether to keep fighting.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Strategy {
+    Reckless,
+    Cautious,
+    Healer,
+}
+
+/// Which CSV report to write.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Report {
+    Daily,
+    Levels,
+    Heroes,
+}
+
+/// Settings for a simulation run.
+#[derive(Debug)]
+pub struct Options {
+    /// Number of heroes to simulate
+    heroes: usize,
+    /// Number of game days to play
+    days: usize,
+    strategy: Strategy,
+    /// Percentage of maximum HP below which a cautious hero stops or a healer hero heals
+    threshold: i32,
+    report: Report,
+}
+
+impl Options {
+    /// Parse the options that follow the `simulate` command.
+    pub fn parse(args: &[String]) -> Result<Options, String> {
+        let mut options = Options { heroes: 1000, days: 30, strategy: Strategy::Cautious, threshold: 50, report: Report::Daily };
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            let value = args.next().ok_or_else(|| format!("{} needs a value", arg))?;
+            let number = || value.parse::<usize>().map_err(|_| format!("Invalid value '{}' for {}: expected a number", value, arg));
+            match arg.as_str() {
+                "--heroes" => options.heroes = number()?,
+                "--days" => options.days = number()?,
+                "--threshold" => {
+                    options.threshold = match number()? {
+                        percent @ 0..=100 => percent as i32,
+                        _ => return Err(format!("Invalid value '{}' for {}: expected a percentage from 0 to 100", value, arg)),
+                    };
+                }
+                "--strategy" => {
+                    options.strategy = match value.as_str() {
+                        "reckless" => Strategy::Reckless,
+                        "cautious" => Strategy::Cautious,
+                        "healer" => Strategy::Healer,
+                        _ => return Err(format!("Unknown strategy '{}'. Available strategies: reckless, cautious, healer", value)),
+                    }
+                }
+                "--report" => {
+                    options.report = match value.as_str() {
+                        "daily" => Report::Daily,
+                        "levels" => Report::Levels,
+                        "heroes" => Report::Heroes,
+                        _ => return Err(format!("Unknown report '{}'. Available reports: daily, levels, heroes", value)),
+                    }
+                }
+                _ => return Err(format!("Unknown simulate option '{}'", arg)),
+            }
+        }
+        Ok(options)
+    }
+}
+
+/// A simulated hero and what happened to them.
+struct Hero {
+    player: Player,
+    fights: i32,
+    deaths: i32,
+    /// The day each level was first reached, indexed by level
+    level_days: Vec<Option<usize>>,
+    /// The day the Red Dragon was first slain
+    first_win: Option<usize>,
+}
+
+/// What happened to all the heroes on one day.
+#[derive(Default)]
+struct Day {
+    fights: i32,
+    deaths: i32,
+    levels: Vec<i32>,
+    gold: Vec<i32>,
+    dragon_kills: i32,
+}
+
+impl Hero {
+    /// A freshly created hero.
+    fn new() -> Hero {
+        let mut player = Player { name: "Simulated".to_string(), ..Player::default() };
+        player.reset_to_starting_stats();
+        Hero { player, fights: 0, deaths: 0, level_days: vec![None, Some(0)], first_win: None }
+    }
+
+    /// Whether the hero is hurt badly enough for the strategy to do something about it.
+    fn wounded(&self, threshold: i32) -> bool {
+        self.player.current_hp * 100 < self.player.max_hp * threshold || self.player.poison > 0
+    }
+
+    /// Visit the healer, paying for each treatment the hero can afford.
+    ///
+    /// Like the real healer, wounds are healed all at once or not at all, so a
+    /// hero who can't pay for every missing HP leaves with none restored.
+    fn visit_healer(&mut self) {
+        let rules = rules();
+        let player = &mut self.player;
+        if player.poison > 0 && player.gold >= rules.cure_poison_price {
+            player.gold -= rules.cure_poison_price;
+            player.poison = 0;
+        }
+        let heal_price = (player.max_hp - player.current_hp) * rules.healer_price_per_hp;
+        if player.gold >= heal_price {
+            player.gold -= heal_price;
+            player.current_hp = player.max_hp;
+        }
+    }
+
+    /// Play one game day, recording what happened in `today`.
+    fn play_day(&mut self, day: usize, options: &Options, today: &mut Day, rng: &mut GameRng) {
+        // The daily reset
+        let player = &mut self.player;
+        player.forest_fights = rules().max_daily_forest_fights;
+        player.alive = true;
+        player.current_hp = player.max_hp;
+        player.poison = 0;
+
+        while self.player.forest_fights > 0 && self.player.alive {
+            if options.strategy == Strategy::Healer && self.wounded(options.threshold) {
+                self.visit_healer();
+            }
+            if options.strategy != Strategy::Reckless && self.wounded(options.threshold) {
+                break;
+            }
+            let outcome = if self.player.level >= rules().red_dragon_level {
+                let outcome = forest::fight_dragon_unattended(&mut self.player, rng);
+                if outcome == Unattended::Won {
+                    today.dragon_kills += 1;
+                    self.first_win.get_or_insert(day);
+                }
+                outcome
+            } else {
+                forest::fight_monster_unattended(&mut self.player, rng)
+            };
+            self.fights += 1;
+            today.fights += 1;
+            if outcome == Unattended::Died {
+                self.deaths += 1;
+                today.deaths += 1;
+            }
+            while self.level_days.len() <= self.player.level as usize {
+                self.level_days.push(Some(day));
+            }
+        }
+        today.levels.push(self.player.level);
+        today.gold.push(self.player.gold);
+    }
+}
+
+/// The mean of some values, or 0 if there are none.
+fn mean(values: &[i32]) -> f64 {
+    if values.is_empty() { 0.0 } else { values.iter().map(|&v| f64::from(v)).sum::<f64>() / values.len() as f64 }
+}
+
+/// The median of some values, or 0 if there are none.
+fn median(values: &[i32]) -> i32 {
+    let mut sorted = values.to_vec();
+    sorted.sort_unstable();
+    sorted.get(sorted.len() / 2).copied().unwrap_or(0)
+}
+
+/// Run the simulation and write the chosen report to stdout as CSV.
+pub fn run(options: &Options, rng: &mut GameRng) {
+    let mut heroes: Vec<Hero> = (0..options.heroes).map(|_| Hero::new()).collect();
+    let mut days: Vec<Day> = Vec::with_capacity(options.days);
+    for day in 1..=options.days {
+        let mut today = Day::default();
+        for hero in &mut heroes {
+            hero.play_day(day, options, &mut today, rng);
+        }
+        days.push(today);
+    }
+
+    match options.report {
+        Report::Daily => {
+            println!("day,fights,deaths,death_rate,mean_level,max_level,mean_gold,median_gold,dragon_kills,heroes_won");
+            for (i, day) in days.iter().enumerate() {
+                let won = heroes.iter().filter(|h| h.first_win.is_some_and(|d| d <= i + 1)).count();
+                println!(
+                    "{},{},{},{:.4},{:.2},{},{:.1},{},{},{}",
+                    i + 1,
+                    day.fights,
+                    day.deaths,
+                    if day.fights == 0 { 0.0 } else { f64::from(day.deaths) / f64::from(day.fights) },
+                    mean(&day.levels),
+                    day.levels.iter().max().copied().unwrap_or(0),
+                    mean(&day.gold),
+                    median(&day.gold),
+                    day.dragon_kills,
+                    won
+                );
+            }
+        }
+        Report::Levels => {
+            println!("level,heroes_reached,mean_days,median_days,min_days,max_days");
+            let top = heroes.iter().map(|h| h.level_days.len()).max().unwrap_or(0);
+            for level in 2..top {
+                let reached: Vec<i32> = heroes.iter().filter_map(|h| h.level_days.get(level).copied().flatten()).map(|d| d as i32).collect();
+                println!(
+                    "{},{},{:.2},{},{},{}",
+                    level,
+                    reached.len(),
+                    mean(&reached),
+                    median(&reached),
+                    reached.iter().min().copied().unwrap_or(0),
+                    reached.iter().max().copied().unwrap_or(0)
+                );
+            }
+        }
+        Report::Heroes => {
+            println!("hero,level,exp,gold,fights,deaths,dragon_kills,first_win_day");
+            for (i, hero) in heroes.iter().enumerate() {
+                let p = &hero.player;
+                let first_win = hero.first_win.map(|d| d.to_string()).unwrap_or_default();
+                println!("{},{},{},{},{},{},{},{}", i + 1, p.level, p.exp, p.gold, hero.fights, hero.deaths, p.dragon_kills, first_win);
+            }
+        }
+    }
+
+    let fights: i32 = days.iter().map(|d| d.fights).sum();
+    let deaths: i32 = days.iter().map(|d| d.deaths).sum();
+    let won = heroes.iter().filter(|h| h.first_win.is_some()).count();
+    eprintln!(
+        "Simulated {} heroes for {} days ({:?} strategy): {} fights, {:.1}% ended in death, {} heroes slew the Red Dragon.",
+        options.heroes,
+        options.days,
+        options.strategy,
+        fights,
+        if fights == 0 { 0.0 } else { f64::from(deaths) * 100.0 / f64::from(fights) },
+        won
+    );
+}
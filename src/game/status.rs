@@ -0,0 +1,83 @@
+//! Status effects suffered in combat: poison, stun and bleeding.
+//!
+//! Some monsters leave more than a wound. Poison is stored on the player and
+//! burns every combat round, in this fight and the ones after it, until it is
+//! cured by the healer, an antidote or the daily reset. Stun and bleeding only
+//! last for the fight in progress and are tracked by `Conditions`.
+use serde::Deserialize;
+use crate::game::Player;
+
+/// Rounds a bleeding wound lasts.
+const BLEED_ROUNDS: i32 = 3;
+
+/// A status effect a monster can inflict.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Status {
+    /// Damage every round until cured
+    Poison,
+    /// The next turn is lost
+    Stun,
+    /// Damage every round for a few rounds
+    Bleeding,
+}
+
+/// The effects a hero is suffering in the fight in progress, besides poison.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Conditions {
+    /// Whether the hero loses their next turn
+    stunned: bool,
+    /// Damage per round from bleeding
+    bleeding: i32,
+    /// Rounds of bleeding left
+    bleed_rounds: i32,
+}
+
+impl Conditions {
+    /// Inflict a status effect of the given strength on the player and describe it.
+    ///
+    /// A stronger dose of poison or a deeper wound replaces a weaker one.
+    pub fn afflict(&mut self, player: &mut Player, status: Status, strength: i32) -> &'static str {
+        let strength = strength.max(1);
+        match status {
+            Status::Poison => {
+                player.poison = player.poison.max(strength);
+                "You have been poisoned!"
+            }
+            Status::Stun => {
+                self.stunned = true;
+                "You are stunned!"
+            }
+            Status::Bleeding => {
+                self.bleeding = self.bleeding.max(strength);
+                self.bleed_rounds = BLEED_ROUNDS;
+                "You are bleeding!"
+            }
+        }
+    }
+
+    /// Apply a round of poison and bleeding to the player.
+    ///
+    /// # Returns
+    ///
+    /// The damage taken this round (0 if the player is not suffering).
+    pub fn tick(&mut self, player: &mut Player) -> i32 {
+        let mut damage = player.poison;
+        if self.bleed_rounds > 0 {
+            damage += self.bleeding;
+            self.bleed_rounds -= 1;
+        }
+        player.current_hp = (player.current_hp - damage).max(0);
+        damage
+    }
+
+    /// Whether the player will lose their next turn, without using up the stun.
+    pub fn is_stunned(&self) -> bool {
+        self.stunned
+    }
+
+    /// Whether the player is stunned this turn; the stun wears off once the turn is lost.
+    pub fn stunned(&mut self) -> bool {
+        std::mem::take(&mut self.stunned)
+    }
+}
@@ -1,15 +1,21 @@
 //! Town menu: presents options to the player and calls the appropriate game functions.
 
 use colored::Colorize;
-use crate::ui::ansi_art; // Import ANSI art
-use crate::game::{Player, forest, pvp, romance};
-use crate::db;
+use crate::ui::{ansi_art, session}; // Import ANSI art
+use crate::game::{Player, bounty, buffs, chat, forest, healer, inventory, leaderboard, news, online, profile, pvp, romance};
+use crate::game::rng::GameRng;
 use sqlx::PgPool;
 
-pub async fn main_menu(conn: &PgPool, player: &mut Player) {
-    // Main loop for the town (runs until player quits or dies)
+pub async fn main_menu(conn: &PgPool, player: &mut Player, rng: &mut GameRng) {
+    // Players who used up today's time allowance must wait for the daily reset
+    if session::time_left().is_some_and(|left| left.is_zero()) {
+        println!("You have used all of your time for today. Come back tomorrow!");
+        return;
+    }
+    // Main loop for the town (runs until player quits, dies, or the session expires)
 	println!("{}", ansi_art::TOWN_SQUARE);
-    while player.alive {
+    while player.alive && !session::expired() {
+        online::set_location(conn, player, "Town Square").await;
         // Display player status summary at the top of each loop
         println!("\n======================================================");
         println!(
@@ -38,24 +44,29 @@ pub async fn main_menu(conn: &PgPool, player: &mut Player) {
         println!("{}", "3. Duel another player".red());
         println!("{}", "4. View your character".blue());
         println!("{}", "5. Read Daily News".cyan());
-        println!("{}", "6. Leaderboard".yellow());
+        println!("{}", "6. Leaderboards".yellow());
         println!("7. Save and Quit");
+        println!("{}", "8. Who's Online".bright_blue());
+        println!("{}", "9. Send a message".bright_cyan());
+        println!("{}", "10. Bounty Board".bright_red());
+        println!("{}", "11. Inventory".bright_green());
+        println!("{}", "12. Healer's Hut".bright_white());
 
         let choice = crate::ui::prompt("What would you like to do? ");
         match choice.trim() {
             "1" => {
-                forest::explore_forest(conn, player).await;
+                forest::explore_forest(conn, player, rng).await;
                 if !player.alive {
                     // Player died in the forest
                     break;
                 }
             }
             "2" => {
-                romance::visit_tavern(conn, player).await;
+                romance::visit_tavern(conn, player, rng).await;
                 // Visiting tavern should not kill the player, so nothing special to check
             }
             "3" => {
-                pvp::challenge_player(conn, player).await;
+                pvp::challenge_player(conn, player, rng).await;
                 if !player.alive {
                     // Player died in a duel
                     break;
@@ -67,8 +78,11 @@ pub async fn main_menu(conn: &PgPool, player: &mut Player) {
                 println!("Name: {}", player.name);
                 println!("Level: {} (Exp: {}/{})", player.level, player.exp, player.xp_to_next_level());
                 println!("Health: {}/{}", player.current_hp, player.max_hp);
+                if player.poison > 0 {
+                    println!("Poisoned: {} damage per combat round until cured", player.poison);
+                }
                 println!("Attack: {}  Defense: {}", player.attack, player.defense);
-                println!("Gold: {}", player.gold);
+                println!("Gold: {}  Gems: {}", player.gold, player.gems);
                 if !player.spouse.is_empty() {
                     println!("Spouse: {}", player.spouse);
                 } else {
@@ -77,48 +91,46 @@ pub async fn main_menu(conn: &PgPool, player: &mut Player) {
                 // Romance points and daily fights (for debug/interest)
                 println!("Romance points (with Violet): {}", player.romance);
                 println!("Forest fights remaining today: {}", player.forest_fights);
+                println!("Player fights remaining today: {}", player.player_fights);
+                println!("Red Dragon kills this season: {}", player.dragon_kills);
+                println!("PvP rating: {}", player.rating);
+                if let Some(left) = session::time_left() {
+                    println!("Time left today: {} minute(s)", left.as_secs().div_ceil(60));
+                }
+                buffs::show_active(conn, player.id).await;
+                profile::show_lifetime_stats(conn, player.id).await;
                 let _ = crate::ui::prompt("Press Enter to continue...");
             }
             "5" => {
-                // Read daily news log
-                println!("\nDaily News Bulletin:");
-                match db::get_latest_events(conn, 10).await {
-                    Ok(events) => {
-                        if events.is_empty() {
-                            println!("No news yet today.");
-                        } else {
-                            for (date, msg) in events {
-                                println!("[{}] {}", date, msg);
-                            }
-                        }
-                    }
-                    Err(err) => println!("Error loading news: {}", err),
-                }
-                let _ = crate::ui::prompt("Press Enter to return to town...");
+                // Read daily news, grouped by day and page by page
+                news::read_news(conn, player).await;
             }
             "6" => {
-                // Show top players leaderboard
-                println!("\nHall of Fame - Top Heroes:");
-                match db::get_top_players(conn, 10).await {
-                    Ok(list) => {
-                        if list.is_empty() {
-                            println!("No players to display.");
-                        } else {
-                            for (rank, info) in list.iter().enumerate() {
-                                println!("{}. {} - Level {}", rank + 1, info.name, info.level);
-                            }
-                        }
-                    }
-                    Err(err) => println!("Could not retrieve leaderboard: {}", err),
-                }
-                let _ = crate::ui::prompt("Press Enter to continue...");
+                // Browse the leaderboards and player listing
+                leaderboard::show_leaderboards(conn).await;
             }
             "7" => {
                 println!("Saving your progress...");
                 break;
             }
+            "8" => {
+                online::show_whos_online(conn).await;
+                let _ = crate::ui::prompt("Press Enter to continue...");
+            }
+            "9" => {
+                chat::send_message(conn, player).await;
+            }
+            "10" => {
+                bounty::visit_bounty_board(conn, player).await;
+            }
+            "11" => {
+                inventory::show_inventory(conn, player).await;
+            }
+            "12" => {
+                healer::visit_healer(conn, player).await;
+            }
             _ => {
-                println!("Invalid choice. Please enter a number from 1 to 7.");
+                println!("Invalid choice. Please enter a number from 1 to 12.");
             }
         }
     } // end while
@@ -13,7 +13,8 @@
 //!
 //! ## Implementation Details
 //!
-//! The application is structured into three main modules:  
+//! The application is structured into four main modules:  
+//! - `config`: Loads the game rules from the TOML configuration file  
 //! - `db`: Handles database operations including player data persistence and daily resets  
 //! - `game`: Contains the game logic for different areas (town, forest, pvp, romance)  
 //! - `ui`: Provides user interface utilities for display and input  
@@ -21,6 +22,8 @@
 //! The game uses Tokio for asynchronous operations, particularly for database access,
 //! while maintaining a synchronous interface for the main game loop.
 
+/// Configuration module for game rules loaded from a TOML file
+mod config;
 /// Database module for player persistence and game state management
 mod db;
 /// Game logic module containing gameplay mechanics and player interactions
@@ -30,7 +33,43 @@ mod ui;
 
 use crate::db::verify_password;
 use chrono::Local;
-use tokio;
+use sqlx::PgPool;
+
+/// Command-line arguments.
+struct Args {
+    /// Administrative command to run instead of an interactive session
+    command: Option<String>,
+    /// Arguments for the command (the recording for `record` and `replay`, options for `simulate`)
+    operands: Vec<String>,
+    /// Seed for the game's random number generator
+    seed: Option<u64>,
+    /// Whether `replay` should play the session again and compare, rather than print it
+    verify: bool,
+    /// File to log every random draw to (used by the session recorder)
+    rng_log: Option<String>,
+}
+
+impl Args {
+    /// Parse the arguments that follow the program name.
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Args, String> {
+        let mut parsed = Args { command: None, operands: Vec::new(), seed: None, verify: false, rng_log: None };
+        while let Some(arg) = args.next() {
+            if arg == "--seed" {
+                let value = args.next().ok_or("--seed needs a number")?;
+                parsed.seed = Some(value.parse().map_err(|_| format!("Invalid seed '{}': expected a number", value))?);
+            } else if arg == "--verify" {
+                parsed.verify = true;
+            } else if arg == "--rng-log" {
+                parsed.rng_log = Some(args.next().ok_or("--rng-log needs a file name")?);
+            } else if parsed.command.is_none() {
+                parsed.command = Some(arg);
+            } else {
+                parsed.operands.push(arg);
+            }
+        }
+        Ok(parsed)
+    }
+}
 
 /// Main entry point for the Legend of the Red Dragon game.
 /// 
@@ -40,12 +79,89 @@ use tokio;
 /// 3. Displays the game title and welcome message
 /// 4. Handles player authentication (login or account creation)
 /// 5. Launches the main game loop
-/// 6. Saves player data on exit
+/// 6. Saves player data on exit (progress is also saved after every action)
+///
+/// When started with a command argument the game runs that administrative
+/// command instead of an interactive session:
+/// - `new-season`: archive the current season and reset every hero
+/// - `maintenance`: run the daily reset at every game-day boundary until stopped
+/// - `record FILE`: play a session while recording it to FILE
+/// - `replay FILE`: print a recorded session; with `--verify`, play it again and compare
+/// - `simulate [OPTIONS]`: simulate heroes fighting in the forest and write a balance report as CSV
+///
+/// `--seed N` seeds the game's random number generator so the session's dice
+/// can be reproduced (overriding the `[session] seed` setting).
 #[tokio::main]
 async fn main() {
+    let args = match Args::parse(std::env::args().skip(1)) {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    };
+    let command = args.command;
+    // The dice are random unless a seed was given on the command line or in the configuration
+    let seed = || args.seed.or(config::get().session.seed).unwrap_or_else(rand::random);
+
     // Clear the terminal screen for a clean start
     // This ensures the game UI begins with a fresh display
-    ui::clear_screen();
+    if command.is_none() {
+        ui::clear_screen();
+    }
+
+    // Load the game rules before anything consults them
+    // A missing file means the classic defaults; a broken one is fatal so typos are not ignored
+    if let Err(e) = config::load() {
+        eprintln!("Failed to load configuration: {}", e);
+        std::process::exit(1);
+    }
+    if let Err(e) = game::items::load() {
+        eprintln!("Failed to load item catalog: {}", e);
+        std::process::exit(1);
+    }
+    if let Err(e) = game::monsters::load() {
+        eprintln!("Failed to load monster catalog: {}", e);
+        std::process::exit(1);
+    }
+
+    // Recording and replaying run the game in a child process, which opens its own database connection
+    if let Some(command @ ("record" | "replay")) = command.as_deref() {
+        let [file] = args.operands.as_slice() else {
+            eprintln!("Usage: {} FILE", if command == "record" { "record [--seed N]" } else { "replay [--verify]" });
+            std::process::exit(1);
+        };
+        let result = match (command, args.verify) {
+            ("record", _) => ui::recording::record(file, seed()),
+            (_, false) => ui::recording::play_back(file),
+            (_, true) => ui::recording::verify(file).map(|matched| {
+                if !matched {
+                    std::process::exit(1);
+                }
+            }),
+        };
+        if let Err(e) = result {
+            eprintln!("Failed to {} {}: {}", command, file, e);
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    // Balance simulations need the rules and the monsters, but no database
+    if command.as_deref() == Some("simulate") {
+        match game::simulate::Options::parse(&args.operands) {
+            Ok(options) => game::simulate::run(&options, &mut game::rng::seeded(seed())),
+            Err(e) => {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+    if let Some(operand) = args.operands.first() {
+        eprintln!("Unexpected argument '{}'", operand);
+        std::process::exit(1);
+    }
 
     // Initialize the PostgreSQL database connection pool and apply schema if needed
     // This establishes the connection to the database and ensures all required tables exist
@@ -54,10 +170,27 @@ async fn main() {
         Ok(pool) => pool,
         Err(e) => {
             eprintln!("Failed to initialize database: {}", e);
-            return;
+            std::process::exit(1);
         }
     };
 
+    // Administrative commands run without starting an interactive session
+    if let Some(command) = command {
+        match command.as_str() {
+            "new-season" => {
+                if !game::season::end_season(&conn).await {
+                    std::process::exit(1);
+                }
+            }
+            "maintenance" => game::calendar::run_daily_maintenance(&conn).await,
+            other => {
+                eprintln!("Unknown command '{}'. Available commands: new-season, maintenance, record, replay, simulate", other);
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+
     // Perform daily reset operations if a new day has started since last reset
     // This includes resetting forest fights, reviving dead players, restoring health, etc.
     if let Err(e) = db::daily_reset(&conn).await {
@@ -70,8 +203,28 @@ async fn main() {
     println!("  By DoubleGate -+-+-+- ver.0.2.0 -+-+-+- March 8th, 2025");
     println!(); // blank line for better readability
 
+    // Sessions left idle at any prompt, including the login, are closed after the configured timeout
+    ui::session::apply_idle_timeout();
+
+    // Every roll of the dice is drawn from the session's generator, logged when the session is being recorded
+    let mut rng = match &args.rng_log {
+        Some(path) => match game::rng::logged(seed(), path) {
+            Ok(rng) => rng,
+            Err(e) => {
+                eprintln!("Failed to open random draw log {}: {}", path, e);
+                std::process::exit(1);
+            }
+        },
+        None => game::rng::seeded(seed()),
+    };
+
     // User authentication loop: continues until a valid login occurs (either an existing user logs in or a new account is created)
     let player = loop {
+        // Stop if the connection dropped or went idle before anyone logged in
+        if ui::session::expired() {
+            return;
+        }
+
         // Prompt for player name
         let name_input = ui::prompt("Enter your name: ");
         let name = name_input.trim().to_string();
@@ -92,7 +245,7 @@ async fn main() {
 
         // Attempt to retrieve the player by name (case-insensitive)
         match db::get_player_by_name(&conn, &name).await {
-            Ok(Some(mut player)) => {
+            Ok(Some(player)) => {
                 // Existing account found: verify password if one is set
                 if !player.password.trim().is_empty() {
                     let pass = ui::prompt("Enter your password: ");
@@ -102,16 +255,10 @@ async fn main() {
                     }
                 }
                 println!("\nHello, {}!", player.name);
-                // Update last login timestamp to now
-                player.last_login = Local::now().naive_local();
-                // Launch main game menu
-                game::town::main_menu(&conn, &mut player).await;
-                // Save player data after gameplay
-                if let Err(e) = db::update_player(&conn, &player).await {
-                    println!("Failed to save player data: {}", e);
+                match play_session(&conn, player, &mut rng).await {
+                    Some(player) => break player,
+                    None => continue,
                 }
-                // Exit loop and keep final player data
-                break player;
             }
             Ok(None) => {
                 println!("No account found with the name '{}'.", name);
@@ -120,14 +267,12 @@ async fn main() {
                     let new_pass = ui::prompt("Enter a password (or leave blank): ");
                     let new_pass_trimmed = new_pass.trim();
                     match db::create_player(&conn, &name, new_pass_trimmed).await {
-                        Ok(mut new_player) => {
+                        Ok(new_player) => {
                             println!("Account '{}' created successfully!", new_player.name);
-                            new_player.last_login = Local::now().naive_local();
-                            game::town::main_menu(&conn, &mut new_player).await;
-                            if let Err(e) = db::update_player(&conn, &new_player).await {
-                                println!("Failed to save new player data: {}", e);
+                            match play_session(&conn, new_player, &mut rng).await {
+                                Some(new_player) => break new_player,
+                                None => continue,
                             }
-                            break new_player;
                         }
                         Err(e) => {
                             println!("Error creating account: {:?}", e);
@@ -148,3 +293,67 @@ async fn main() {
     // Farewell message when the player exits the game
     println!("\nThank you for playing! Goodbye, {}.", player.name);
 }
+
+/// Play a session as an authenticated player.
+///
+/// This function:
+/// 1. Claims the player's online lock so no other session can play them
+/// 2. Reloads the player, in case a session that just ended saved them
+/// 3. Starts the play time clock and the listener for chat and breaking news
+/// 4. Runs the town menu until the player quits, dies, or the session expires
+/// 5. Saves the player and releases the lock
+///
+/// # Returns
+///
+/// The final player data, or `None` if the session could not start (for example
+/// because the player is already online elsewhere).
+async fn play_session(conn: &PgPool, mut player: game::Player, rng: &mut game::rng::GameRng) -> Option<game::Player> {
+    // Give the session its own clock and notice queue, shared with the tasks started below
+    let session = ui::session::begin();
+    // Claim the hero so a second session cannot play (and overwrite) them at the same time
+    let lock = match game::online::SessionLock::acquire(conn, player.id, session.clone()).await {
+        Ok(Some(lock)) => lock,
+        Ok(None) => {
+            println!("{} is already playing on another node. Please try again later.", player.name);
+            return None;
+        }
+        Err(e) => {
+            println!("Error retrieving player: {}", e);
+            return None;
+        }
+    };
+    // Reload now that we own the hero
+    if let Ok(Some(fresh)) = db::get_player_by_id(conn, player.id).await {
+        player = fresh;
+    }
+    // Update last login timestamp to now
+    player.last_login = Local::now().naive_local();
+    // Start counting against today's play time allowance
+    ui::session::start_daily_clock(player.time_used);
+    if let Err(e) = db::record_day_played(conn, player.id).await {
+        eprintln!("Failed to record day played: {}", e);
+    }
+    // Deliver messages from other nodes and news from the rest of the realm between prompts
+    let listener = async {
+        let feeds = vec![game::chat::feed(conn, player.id).await?, game::news::feed(conn, player.id).await?];
+        game::listener::Listener::start(conn, session, feeds).await
+    };
+    let listener = match listener.await {
+        Ok(listener) => Some(listener),
+        Err(e) => {
+            eprintln!("Chat and breaking news are unavailable: {}", e);
+            None
+        }
+    };
+
+    // Launch main game menu
+    game::town::main_menu(conn, &mut player, rng).await;
+
+    // Save player data after gameplay, including the time spent
+    game::save_player(conn, &mut player).await;
+    if let Some(listener) = listener {
+        listener.stop();
+    }
+    lock.release(conn).await;
+    Some(player)
+}
@@ -113,6 +113,7 @@ pub const DUEL_SCENE: &str = "
 /// 
 /// This ASCII art is displayed when the player dies in combat,
 /// indicating game over until the next daily reset.
+#[allow(dead_code)]
 pub const GAME_OVER: &str = "
 \x1B[31m+========================================+
 |         \x1B[1;31m💀 YOU HAVE DIED 💀\x1B[0;31m        |
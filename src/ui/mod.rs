@@ -9,16 +9,34 @@
 //! - User input handling with prompts  
 //! - Terminal screen management  
 //! - ANSI color and art display  
+//! - Session time limits and idle timeout  
+//! - Notices (such as chat messages) delivered between prompts  
 //!
 //! ## Implementation Details
 //!
 //! The module uses standard Rust I/O operations for terminal interaction.
 //! ANSI escape sequences are used for text formatting and screen control.
 //! The `ansi_art` submodule contains ASCII art for various game scenes.
+//! Input is read by a background thread so prompts can give up waiting
+//! when the `session` clock says time is up.
 use std::io::{stdin, stdout, Write};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::sync::{Mutex, OnceLock};
+use std::thread;
 
 /// ANSI art and colored text banners for the game
 pub mod ansi_art;
+/// Recording sessions to a file and replaying them
+pub mod recording;
+/// Daily time allowance, idle timeout and notice queue of each session
+pub mod session;
+
+/// Print and clear the notices queued for the current session.
+fn show_notices() {
+    for notice in session::take_notices() {
+        println!("{}", notice);
+    }
+}
 
 /// Print the title banner ASCII art.
 /// 
@@ -31,11 +49,12 @@ pub fn show_title() {
 /// Prompt the user for input, displaying a message, and return the input string.
 /// 
 /// This function:
-/// 1. Displays the provided message
-/// 2. Flushes stdout to ensure the message is shown
-/// 3. Reads a line of input from the user
-/// 4. Trims trailing newlines and carriage returns
-/// 5. Clears the screen after input (for a clean UI)
+/// 1. Shows any queued notices and warns the player if their daily time is running low
+/// 2. Displays the provided message
+/// 3. Flushes stdout to ensure the message is shown
+/// 4. Reads a line of input from the user, waiting no longer than the session allows
+/// 5. Trims trailing newlines and carriage returns
+/// 6. Clears the screen after input (for a clean UI)
 /// 
 /// # Arguments
 /// 
@@ -43,24 +62,60 @@ pub fn show_title() {
 /// 
 /// # Returns
 /// 
-/// The user's input as a String, with trailing newlines removed
-/// 
-/// # Panics
-/// 
-/// Exits the program if reading from stdin fails
+/// The user's input as a String, with trailing newlines removed.
+/// Once the session has expired (time used up, idle too long, or input closed)
+/// an empty string is returned without waiting; callers check `session::expired()`
+/// to leave their menus.
 pub fn prompt(message: &str) -> String {
+    if session::expired() {
+        return String::new();
+    }
+    show_notices();
+    session::warn_if_low();
     print!("{}", message);
     let _ = stdout().flush();
-    let mut input = String::new();
-    if stdin().read_line(&mut input).is_err() {
-        eprintln!("Failed to read input.");
-        std::process::exit(1);
-    }
-    let input = input.trim_end_matches(['\n', '\r']).to_string();
+    let lines = input_lines().lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    let received = match session::input_deadline() {
+        Some(deadline) => lines.recv_timeout(deadline),
+        None => lines.recv().map_err(|_| RecvTimeoutError::Disconnected),
+    };
+    let input = match received {
+        Ok(line) => line.trim_end_matches(['\n', '\r']).to_string(),
+        Err(RecvTimeoutError::Timeout) => {
+            session::expire_for_timeout();
+            return String::new();
+        }
+        Err(RecvTimeoutError::Disconnected) => {
+            session::expire("Connection closed. Saving your progress...");
+            return String::new();
+        }
+    };
     clear_screen();
     input
 }
 
+/// Lines typed by the player, read from stdin by a background thread.
+///
+/// The channel disconnects when stdin reaches end-of-file or fails.
+fn input_lines() -> &'static Mutex<Receiver<String>> {
+    static LINES: OnceLock<Mutex<Receiver<String>>> = OnceLock::new();
+    LINES.get_or_init(|| {
+        let (tx, rx) = mpsc::channel();
+        thread::spawn(move || loop {
+            let mut line = String::new();
+            match stdin().read_line(&mut line) {
+                Ok(0) | Err(_) => break,
+                Ok(_) => {
+                    if tx.send(line).is_err() {
+                        break;
+                    }
+                }
+            }
+        });
+        Mutex::new(rx)
+    })
+}
+
 /// Clear the terminal screen (if supported) and move cursor to home position.
 /// 
 /// Uses ANSI escape sequences to:
@@ -0,0 +1,245 @@
+//! Session recording and replay.
+//!
+//! `record FILE` plays an ordinary interactive session in a child process and
+//! writes down everything that happens: the seed of the game's random number
+//! generator, every line the player types, everything the game prints, and
+//! every random draw. Because the game only ever talks through stdin and
+//! stdout, the recorder sits between the terminal and the child and needs no
+//! help from the rest of the code.
+//!
+//! A recording is a JSON Lines file with one entry per line:
+//!
+//! ```text
+//! {"seed":12345}
+//! {"output":"Enter your name: "}
+//! {"input":"Seth"}
+//! ...
+//! {"rng_draws":[8391204, 17, ...]}
+//! ```
+//!
+//! `replay FILE` prints the recording back as the player saw it, which is handy
+//! for bug reports. `replay --verify FILE` plays the session again with the
+//! same seed and inputs and reports the first line of output, or the first
+//! random draw, that comes out differently. The database must be in the same
+//! state as when the session was recorded (for example a freshly created test
+//! database) for the output to match.
+use std::env;
+use std::fs::{self, File};
+use std::io::{self, stdin, stdout, BufRead, BufReader, BufWriter, Read, Write};
+use std::process::{Child, Command, Stdio};
+use std::sync::mpsc;
+use std::thread;
+use colored::Colorize;
+use serde::{Deserialize, Serialize};
+
+/// One line of a recording.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum Entry {
+    /// The seed the session's random number generator started from
+    Seed(u64),
+    /// A line typed by the player, without its newline
+    Input(String),
+    /// Text printed by the game
+    Output(String),
+    /// Every number drawn from the random number generator, in order
+    RngDraws(Vec<u64>),
+}
+
+/// A recording read back from its file.
+#[derive(Default)]
+struct Recording {
+    seed: u64,
+    inputs: Vec<String>,
+    output: String,
+    draws: Vec<u64>,
+    /// Inputs and output in the order they happened, for playback
+    entries: Vec<Entry>,
+}
+
+/// Read a recording file.
+fn load(path: &str) -> io::Result<Recording> {
+    let mut recording = Recording::default();
+    for (number, line) in BufReader::new(File::open(path)?).lines().enumerate() {
+        let entry: Entry = serde_json::from_str(&line?).map_err(|e| {
+            io::Error::new(io::ErrorKind::InvalidData, format!("{}, line {}: {}", path, number + 1, e))
+        })?;
+        match &entry {
+            Entry::Seed(seed) => recording.seed = *seed,
+            Entry::Input(line) => recording.inputs.push(line.clone()),
+            Entry::Output(text) => recording.output.push_str(text),
+            Entry::RngDraws(draws) => recording.draws = draws.clone(),
+        }
+        recording.entries.push(entry);
+    }
+    Ok(recording)
+}
+
+/// Append one entry to a recording.
+fn write_entry(out: &mut impl Write, entry: &Entry) -> io::Result<()> {
+    let line = serde_json::to_string(entry).map_err(io::Error::other)?;
+    writeln!(out, "{}", line)
+}
+
+/// Start the game as a child process with the given seed, logging its random draws to `draws_path`.
+fn spawn_game(seed: u64, draws_path: &str) -> io::Result<Child> {
+    Command::new(env::current_exe()?)
+        .args(["--seed", &seed.to_string(), "--rng-log", draws_path])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+}
+
+/// Wait for a child to exit, treating a failed game as an error.
+fn finish(mut child: Child) -> io::Result<()> {
+    let status = child.wait()?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(io::Error::other(format!("the game exited with {}", status)))
+    }
+}
+
+/// Read the draws a child logged, one per line, and remove the log.
+fn take_draws(draws_path: &str) -> io::Result<Vec<u64>> {
+    let text = fs::read_to_string(draws_path)?;
+    fs::remove_file(draws_path)?;
+    Ok(text.lines().filter_map(|line| line.parse().ok()).collect())
+}
+
+/// Read a child's output as text, without splitting a character between chunks.
+fn read_chunks(mut from: impl Read, mut each: impl FnMut(String) -> bool) {
+    let mut buf = [0u8; 4096];
+    let mut pending = Vec::new();
+    loop {
+        match from.read(&mut buf) {
+            Ok(0) | Err(_) => break,
+            Ok(n) => pending.extend_from_slice(&buf[..n]),
+        }
+        let complete = match std::str::from_utf8(&pending) {
+            Ok(_) => pending.len(),
+            Err(e) if e.error_len().is_none() => e.valid_up_to(),
+            Err(_) => pending.len(),
+        };
+        let text = String::from_utf8_lossy(&pending[..complete]).into_owned();
+        pending.drain(..complete);
+        if !text.is_empty() && !each(text) {
+            break;
+        }
+    }
+    if !pending.is_empty() {
+        each(String::from_utf8_lossy(&pending).into_owned());
+    }
+}
+
+/// Play an interactive session while recording it to `path`.
+pub fn record(path: &str, seed: u64) -> io::Result<()> {
+    let mut out = BufWriter::new(File::create(path)?);
+    write_entry(&mut out, &Entry::Seed(seed))?;
+    let draws_path = format!("{}.rng", path);
+    let mut child = spawn_game(seed, &draws_path)?;
+    let (tx, rx) = mpsc::channel();
+
+    // Pass the player's typing on to the game
+    let mut game_in = child.stdin.take().expect("child stdin is piped");
+    let input_tx = tx.clone();
+    thread::spawn(move || {
+        for line in stdin().lines() {
+            let Ok(line) = line else { break };
+            if writeln!(game_in, "{}", line).is_err() || input_tx.send(Some(Entry::Input(line))).is_err() {
+                break;
+            }
+        }
+    });
+
+    // Show the game's output to the player; the end of it ends the recording
+    let game_out = child.stdout.take().expect("child stdout is piped");
+    thread::spawn(move || {
+        read_chunks(game_out, |text| {
+            print!("{}", text);
+            let _ = stdout().flush();
+            tx.send(Some(Entry::Output(text))).is_ok()
+        });
+        let _ = tx.send(None);
+    });
+
+    while let Ok(Some(entry)) = rx.recv() {
+        write_entry(&mut out, &entry)?;
+    }
+    finish(child)?;
+    write_entry(&mut out, &Entry::RngDraws(take_draws(&draws_path)?))?;
+    out.flush()?;
+    eprintln!("Session recorded to {}.", path);
+    Ok(())
+}
+
+/// Print a recording back as the player saw it, with their typing highlighted.
+pub fn play_back(path: &str) -> io::Result<()> {
+    for entry in load(path)?.entries {
+        match entry {
+            Entry::Output(text) => print!("{}", text),
+            Entry::Input(line) => println!("{}", line.bright_yellow()),
+            Entry::Seed(_) | Entry::RngDraws(_) => {}
+        }
+    }
+    let _ = stdout().flush();
+    Ok(())
+}
+
+/// Play a recorded session again and compare what happens with the recording.
+///
+/// # Returns
+///
+/// `true` if the output and every random draw matched.
+pub fn verify(path: &str) -> io::Result<bool> {
+    let recording = load(path)?;
+    let draws_path = format!("{}.verify.rng", path);
+    let mut child = spawn_game(recording.seed, &draws_path)?;
+    // The game reads its input on a background thread, so it can all be typed ahead
+    let mut game_in = child.stdin.take().expect("child stdin is piped");
+    let inputs = recording.inputs.clone();
+    thread::spawn(move || {
+        for line in inputs {
+            if writeln!(game_in, "{}", line).is_err() {
+                break;
+            }
+        }
+    });
+    let mut output = String::new();
+    read_chunks(child.stdout.take().expect("child stdout is piped"), |text| {
+        output.push_str(&text);
+        true
+    });
+    finish(child)?;
+    let draws = take_draws(&draws_path)?;
+
+    let mut matched = true;
+    if let Some((number, (expected, actual))) = recording.output.lines()
+        .zip(output.lines())
+        .enumerate()
+        .find(|(_, (expected, actual))| expected != actual)
+    {
+        println!("Output differs at line {}:", number + 1);
+        println!("  recorded: {:?}", expected);
+        println!("  replayed: {:?}", actual);
+        matched = false;
+    } else if recording.output.lines().count() != output.lines().count() {
+        println!(
+            "Output differs in length: {} lines recorded, {} replayed.",
+            recording.output.lines().count(),
+            output.lines().count()
+        );
+        matched = false;
+    }
+    if let Some(index) = recording.draws.iter().zip(&draws).position(|(expected, actual)| expected != actual) {
+        println!("Random draw {} differs: {} recorded, {} replayed.", index + 1, recording.draws[index], draws[index]);
+        matched = false;
+    } else if recording.draws.len() != draws.len() {
+        println!("{} random draws recorded, {} replayed.", recording.draws.len(), draws.len());
+        matched = false;
+    }
+    if matched {
+        println!("Replay matches the recording ({} inputs, {} random draws).", recording.inputs.len(), draws.len());
+    }
+    Ok(matched)
+}
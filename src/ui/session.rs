@@ -0,0 +1,190 @@
+//! # Session Clock
+//!
+//! Tracks how long the current player has been connected and enforces the
+//! daily time allowance and idle timeout from the `[session]` configuration.
+//! Each session also keeps the queue of notices (such as chat messages) that
+//! its background tasks deliver between prompts.
+//!
+//! ## Implementation Details
+//!
+//! Limits are enforced at `ui::prompt`: input is awaited with a deadline, and
+//! once the deadline passes the session is marked expired. From then on every
+//! prompt returns an empty answer immediately, and the menu loops check
+//! `expired()` so control unwinds back to `main`, which saves the player.
+//!
+//! `begin` starts a fresh `Session` for a logged-in player and makes it the
+//! current one, which the free functions here and `ui::prompt` act on. The
+//! session's listeners hold their own handle, so nothing they queue or expire
+//! can reach a later session.
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+/// The session the prompts belong to; before login, one with no player.
+static CURRENT: Mutex<Option<Session>> = Mutex::new(None);
+
+/// Mutable state behind the session clock.
+struct SessionState {
+    /// When the player's daily clock started ticking, if it has
+    started: Option<Instant>,
+    /// Time the player had already used today before this session
+    used_before: Duration,
+    /// Total time the player may use today (None = unlimited)
+    allowance: Option<Duration>,
+    /// Maximum wait for input (None = wait forever)
+    idle_timeout: Option<Duration>,
+    /// Remaining minutes at the last low-time warning, to avoid repeating it
+    last_warning: Option<u64>,
+    /// Whether the session has run out of time, gone idle or lost its input
+    expired: bool,
+    /// Lines waiting to be shown to the player at the next prompt
+    notices: Vec<String>,
+}
+
+impl SessionState {
+    const fn new() -> Self {
+        SessionState {
+            started: None,
+            used_before: Duration::ZERO,
+            allowance: None,
+            idle_timeout: None,
+            last_warning: None,
+            expired: false,
+            notices: Vec::new(),
+        }
+    }
+
+    fn used(&self) -> Duration {
+        self.used_before + self.started.map(|s| s.elapsed()).unwrap_or_default()
+    }
+
+    fn time_left(&self) -> Option<Duration> {
+        self.allowance.map(|a| a.saturating_sub(self.used()))
+    }
+}
+
+/// Handle to one player's session, shared with the tasks running alongside it.
+#[derive(Clone)]
+pub struct Session(Arc<Mutex<SessionState>>);
+
+impl Session {
+    fn new() -> Session {
+        Session(Arc::new(Mutex::new(SessionState::new())))
+    }
+
+    fn state(&self) -> MutexGuard<'_, SessionState> {
+        self.0.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Queue a line to be shown to the player before the next prompt.
+    ///
+    /// Background tasks (such as the chat listener) use this to deliver messages
+    /// without interrupting whatever the player is reading or typing.
+    pub fn notify(&self, line: String) {
+        self.state().notices.push(line);
+    }
+
+    /// End the session, telling the player why. Only the first reason is shown.
+    pub fn expire(&self, reason: &str) {
+        let mut state = self.state();
+        if !state.expired {
+            state.expired = true;
+            println!("\n{}", reason);
+        }
+    }
+}
+
+/// The session the prompts currently belong to.
+fn current() -> Session {
+    CURRENT.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).get_or_insert_with(Session::new).clone()
+}
+
+/// Start a new session for a player who has just logged in.
+///
+/// The new session keeps the idle timeout but starts with its own clock and an
+/// empty notice queue. The returned handle is for the session's background tasks.
+pub fn begin() -> Session {
+    let session = Session::new();
+    session.state().idle_timeout = current().state().idle_timeout;
+    *CURRENT.lock().unwrap_or_else(|poisoned| poisoned.into_inner()) = Some(session.clone());
+    session
+}
+
+/// Apply the configured idle timeout to all following prompts.
+pub fn apply_idle_timeout() {
+    let minutes = crate::config::get().session.idle_timeout_minutes;
+    current().state().idle_timeout = (minutes > 0).then(|| Duration::from_secs(minutes as u64 * 60));
+}
+
+/// Start the daily clock for a player who has already played `used_secs` seconds today.
+///
+/// The allowance comes from the `daily_minutes` setting; with no allowance the
+/// clock still runs so the time played is recorded.
+pub fn start_daily_clock(used_secs: i32) {
+    let minutes = crate::config::get().session.daily_minutes;
+    let session = current();
+    let mut state = session.state();
+    state.started = Some(Instant::now());
+    state.used_before = Duration::from_secs(used_secs.max(0) as u64);
+    state.allowance = (minutes > 0).then(|| Duration::from_secs(minutes as u64 * 60));
+    state.last_warning = None;
+}
+
+/// Seconds the player has played today, including this session.
+pub fn time_used_secs() -> i32 {
+    current().state().used().as_secs().min(i32::MAX as u64) as i32
+}
+
+/// Time the player has left today, or `None` if play time is unlimited.
+pub fn time_left() -> Option<Duration> {
+    current().state().time_left()
+}
+
+/// Whether the session has ended and control should return to `main`.
+pub fn expired() -> bool {
+    current().state().expired
+}
+
+/// End the session, telling the player why. Only the first reason is shown.
+pub fn expire(reason: &str) {
+    current().expire(reason);
+}
+
+/// How long the next prompt may wait for input before the session expires.
+pub(super) fn input_deadline() -> Option<Duration> {
+    let session = current();
+    let state = session.state();
+    match (state.idle_timeout, state.time_left()) {
+        (Some(idle), Some(left)) => Some(idle.min(left)),
+        (idle, left) => idle.or(left),
+    }
+}
+
+/// Expire the session for whichever limit was reached while waiting for input.
+pub(super) fn expire_for_timeout() {
+    let out_of_time = time_left().is_some_and(|left| left.is_zero());
+    if out_of_time {
+        expire("Your time for today has run out. Saving your progress...");
+    } else {
+        expire("You have been idle for too long. Saving your progress...");
+    }
+}
+
+/// Warn the player when their daily time is running low, once per minute.
+pub(super) fn warn_if_low() {
+    let threshold = crate::config::get().session.warning_minutes.max(0) as u64;
+    let session = current();
+    let mut state = session.state();
+    let Some(left) = state.time_left() else {
+        return;
+    };
+    let minutes = left.as_secs().div_ceil(60);
+    if minutes <= threshold && state.last_warning != Some(minutes) {
+        state.last_warning = Some(minutes);
+        println!("*** You have {} minute(s) left today. ***", minutes);
+    }
+}
+
+/// Take all queued notices, leaving the queue empty.
+pub(super) fn take_notices() -> Vec<String> {
+    std::mem::take(&mut current().state().notices)
+}
@@ -0,0 +1,284 @@
+//! Scripted sessions played against the real game.
+//!
+//! Each test creates its own database, then runs the `lord_rust` binary with a
+//! fixed seed, types a script of inputs into it and reads everything it prints,
+//! exactly as a player on a terminal would. Tests assert on the screen text and
+//! on the heroes left in the database afterwards, so the login, the town menu
+//! and the forest, tavern and duel flows are covered end to end.
+//!
+//! The tests need a PostgreSQL server they are allowed to create databases on:
+//!
+//! ```sh
+//! TEST_DATABASE_URL=postgres://postgres@localhost/postgres cargo test
+//! ```
+//!
+//! Without `TEST_DATABASE_URL` they are skipped. Each test's database is named
+//! after the test and is left in place afterwards so a failure can be
+//! inspected; it is dropped and recreated on the next run.
+use std::env;
+use std::fs;
+use std::io::{Read, Write};
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use sqlx::{Connection, PgConnection, Row};
+
+/// The rules every scripted session plays by, so the assertions don't depend on the defaults.
+const TEST_CONFIG: &str = r#"
+[rules]
+starting_gold = 100
+drink_price = 10
+max_daily_forest_fights = 10
+max_daily_player_fights = 3
+new_player_protection_hours = 0
+"#;
+
+/// A fresh game world in its own database.
+struct Realm {
+    database_url: String,
+    config_path: PathBuf,
+}
+
+/// What the database holds about a hero after a session.
+#[derive(Debug)]
+struct Hero {
+    level: i32,
+    exp: i32,
+    gold: i32,
+    current_hp: i32,
+    max_hp: i32,
+    attack: i32,
+    forest_fights: i32,
+    player_fights: i32,
+    alive: bool,
+    romance: i32,
+    rating: i32,
+}
+
+impl Realm {
+    /// Create an empty realm for the named test, or `None` if no test database is configured.
+    async fn open(name: &str) -> Option<Realm> {
+        let Ok(admin_url) = env::var("TEST_DATABASE_URL") else {
+            eprintln!("TEST_DATABASE_URL is not set; skipping the {} session test", name);
+            return None;
+        };
+        let database = format!("lord_test_{}", name);
+        let mut admin = PgConnection::connect(&admin_url).await.expect("connect to TEST_DATABASE_URL");
+        sqlx::query(&format!("DROP DATABASE IF EXISTS {} WITH (FORCE)", database))
+            .execute(&mut admin)
+            .await
+            .expect("drop the old test database");
+        sqlx::query(&format!("CREATE DATABASE {}", database))
+            .execute(&mut admin)
+            .await
+            .expect("create the test database");
+        let (server, _) = admin_url.rsplit_once('/').expect("TEST_DATABASE_URL names a database");
+        let config_path = env::temp_dir().join(format!("{}.toml", database));
+        fs::write(&config_path, TEST_CONFIG).expect("write the test configuration");
+        Some(Realm { database_url: format!("{}/{}", server, database), config_path })
+    }
+
+    /// Play one session with the given seed, typing each line of `script`, and return what was shown.
+    ///
+    /// The session ends when the script runs out, which the game treats as a
+    /// closed connection: it saves the hero and exits.
+    fn play(&self, seed: u64, script: &[&str]) -> String {
+        let mut game = Command::new(env!("CARGO_BIN_EXE_lord_rust"))
+            .args(["--seed", &seed.to_string()])
+            .env("DATABASE_URL", &self.database_url)
+            .env("LORD_CONFIG", &self.config_path)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .expect("start the game");
+        let mut input = game.stdin.take().expect("game stdin is piped");
+        for line in script {
+            writeln!(input, "{}", line).expect("type into the game");
+        }
+        drop(input);
+        let mut output = String::new();
+        game.stdout.take().expect("game stdout is piped").read_to_string(&mut output).expect("read the game's output");
+        assert!(game.wait().expect("wait for the game").success(), "the game exited with an error:\n{}", output);
+        strip_ansi(&output)
+    }
+
+    /// Look up a hero by name.
+    async fn hero(&self, name: &str) -> Hero {
+        let mut conn = PgConnection::connect(&self.database_url).await.expect("connect to the test database");
+        let row = sqlx::query(
+            "SELECT level, exp, gold, current_hp, max_hp, attack, forest_fights, player_fights, alive, romance, rating
+             FROM players WHERE name = $1",
+        )
+        .bind(name)
+        .fetch_one(&mut conn)
+        .await
+        .expect("the hero exists");
+        Hero {
+            level: row.get("level"),
+            exp: row.get("exp"),
+            gold: row.get("gold"),
+            current_hp: row.get("current_hp"),
+            max_hp: row.get("max_hp"),
+            attack: row.get("attack"),
+            forest_fights: row.get("forest_fights"),
+            player_fights: row.get("player_fights"),
+            alive: row.get("alive"),
+            romance: row.get("romance"),
+            rating: row.get("rating"),
+        }
+    }
+
+    /// Put items in a hero's pack.
+    async fn give(&self, name: &str, item_id: &str, quantity: i32) {
+        let mut conn = PgConnection::connect(&self.database_url).await.expect("connect to the test database");
+        sqlx::query("INSERT INTO inventory (player_id, item_id, quantity) SELECT id, $2, $3 FROM players WHERE name = $1")
+            .bind(name)
+            .bind(item_id)
+            .bind(quantity)
+            .execute(&mut conn)
+            .await
+            .expect("the hero exists");
+    }
+
+    /// How many of an item a hero carries.
+    async fn carried(&self, name: &str, item_id: &str) -> i32 {
+        let mut conn = PgConnection::connect(&self.database_url).await.expect("connect to the test database");
+        sqlx::query_scalar(
+            "SELECT COALESCE(SUM(i.quantity), 0)::INT FROM inventory i JOIN players p ON p.id = i.player_id
+             WHERE p.name = $1 AND i.item_id = $2",
+        )
+        .bind(name)
+        .bind(item_id)
+        .fetch_one(&mut conn)
+        .await
+        .expect("count the hero's items")
+    }
+}
+
+/// Remove ANSI color and cursor sequences so assertions can match plain text.
+fn strip_ansi(text: &str) -> String {
+    let mut plain = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1B' {
+            // Skip the '[', any parameters, and the final letter
+            for c in chars.by_ref() {
+                if c.is_ascii_alphabetic() {
+                    break;
+                }
+            }
+        } else {
+            plain.push(c);
+        }
+    }
+    plain
+}
+
+/// Create a hero with no password; the session then continues with `script` in town.
+fn new_hero<'a>(name: &'a str, script: &[&'a str]) -> Vec<&'a str> {
+    [name, "Y", ""].iter().chain(script).copied().collect()
+}
+
+#[tokio::test]
+async fn new_hero_is_created_and_saved() {
+    let Some(realm) = Realm::open("new_hero").await else { return };
+    let output = realm.play(1, &new_hero("Seth", &["4", "", "7"]));
+    assert!(output.contains("No account found with the name 'Seth'."));
+    assert!(output.contains("Account 'Seth' created successfully!"));
+    assert!(output.contains("Name: Seth"));
+    assert!(output.contains("Gold: 100"));
+    assert!(output.contains("Thank you for playing! Goodbye, Seth."));
+
+    let seth = realm.hero("Seth").await;
+    assert_eq!((seth.level, seth.exp, seth.gold), (1, 0, 100));
+    assert!(seth.alive);
+}
+
+#[tokio::test]
+async fn returning_hero_must_give_the_right_password() {
+    let Some(realm) = Realm::open("password").await else { return };
+    realm.play(2, &["Jennie", "Y", "secret", "7"]);
+    let output = realm.play(2, &["Jennie", "wrong", "Jennie", "secret", "7"]);
+    assert!(output.contains("Incorrect password for 'Jennie'. Please try again."));
+    assert!(output.contains("Hello, Jennie!"));
+}
+
+#[tokio::test]
+async fn forest_victory_is_rewarded_and_saved() {
+    let Some(realm) = Realm::open("forest_victory").await else { return };
+    // With this seed a Wild Boar falls to five blows; a hero with nothing in their pack just fights
+    let output = realm.play(6, &new_hero("Turgon", &["1", "N", "7"]));
+    assert!(output.contains("You venture into the forest... (10 fights left today)"));
+    assert!(output.contains("A wild Wild Boar appears!"));
+    assert!(output.contains("You gain 13 XP and 11 gold."));
+    assert!(output.contains("You decide to leave the forest and head back to town."));
+
+    let turgon = realm.hero("Turgon").await;
+    assert_eq!((turgon.exp, turgon.gold, turgon.forest_fights), (13, 111, 9));
+    assert!(turgon.alive);
+}
+
+#[tokio::test]
+async fn death_in_the_forest_ends_the_day() {
+    let Some(realm) = Realm::open("forest_death").await else { return };
+    // With this seed an Ogre is too much for a new hero
+    let output = realm.play(12, &new_hero("Dorin", &["1"]));
+    assert!(output.contains("A wild Ogre appears!"));
+    assert!(output.contains("You have perished for today."));
+
+    let dorin = realm.hero("Dorin").await;
+    assert!(!dorin.alive);
+    assert_eq!((dorin.current_hp, dorin.exp), (0, 0));
+    // The dead stay dead until the daily reset
+    let again = realm.play(12, &["Dorin", "1"]);
+    assert!(again.contains("You have perished for today."));
+    assert!(!again.contains("You venture into the forest"));
+}
+
+#[tokio::test]
+async fn tavern_drink_and_flirting_are_saved() {
+    let Some(realm) = Realm::open("tavern").await else { return };
+    let output = realm.play(4, &new_hero("Barak", &["2", "2", "1", "7", "7"]));
+    assert!(output.contains("You enter the Dark Cloak Tavern."));
+    assert!(output.contains("Current HP:"));
+
+    let barak = realm.hero("Barak").await;
+    assert_eq!(barak.gold, 90);
+    assert_eq!(barak.current_hp, barak.max_hp);
+    assert_eq!(barak.romance, 1);
+}
+
+#[tokio::test]
+async fn duel_between_two_heroes_is_settled() {
+    let Some(realm) = Realm::open("duel").await else { return };
+    realm.play(5, &new_hero("Arthur", &["7"]));
+    let output = realm.play(5, &new_hero("Lancelot", &["3", "1", "", ""]));
+    assert!(output.contains("1. Arthur (Level 1)"));
+    assert!(output.contains("You challenge Arthur to a duel!"));
+
+    // With this seed the challenger wins, loots half of Arthur's gold and takes rating from him
+    assert!(output.contains("You have defeated Arthur!"));
+    assert!(output.contains("You loot 50 gold from Arthur."));
+
+    let arthur = realm.hero("Arthur").await;
+    let lancelot = realm.hero("Lancelot").await;
+    assert_eq!((lancelot.player_fights, lancelot.gold), (2, 150));
+    assert!(lancelot.alive);
+    assert_eq!((arthur.player_fights, arthur.gold), (3, 50));
+    assert!(!arthur.alive);
+    assert!(lancelot.rating > arthur.rating);
+}
+
+#[tokio::test]
+async fn using_an_item_takes_it_from_the_pack_and_keeps_its_effect() {
+    let Some(realm) = Realm::open("use_item").await else { return };
+    realm.play(7, &new_hero("Elwing", &["7"]));
+    let before = realm.hero("Elwing").await;
+    realm.give("Elwing", "strength_elixir", 2).await;
+
+    let output = realm.play(7, &["Elwing", "", "11", "1", "7"]);
+    assert!(output.contains("1. Strength Elixir x2"));
+
+    let elwing = realm.hero("Elwing").await;
+    assert_eq!(elwing.attack, before.attack + 1);
+    assert_eq!(realm.carried("Elwing", "strength_elixir").await, 1);
+}